@@ -0,0 +1,238 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::credentials::{constant_time_eq, hash_token, to_hex};
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+
+/// API 密钥前缀
+pub const API_KEY_PREFIX: &str = "sk_";
+/// 密钥编号长度（字节，十六进制后为 16 个字符）
+const API_KEY_ID_BYTES: usize = 8;
+/// 密钥长度（字节，十六进制后为 64 个字符）
+const API_KEY_SECRET_BYTES: usize = 32;
+
+/// API 密钥记录
+///
+/// 只保存密钥的 SHA-256 哈希，密钥明文只在创建时返回一次。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub key_id: String,
+    pub name: String,
+    pub secret_hash: String,
+    pub permissions: Vec<String>,
+    pub created_at: u64,
+    /// 过期时间（Unix 秒），None 表示永不过期
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// 轮换后替代该密钥的新密钥编号
+    #[serde(default)]
+    pub replaced_by: Option<String>,
+}
+
+impl ApiKeyRecord {
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// 新创建的 API 密钥
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+    /// 完整密钥 `sk_<key_id>_<secret>`，只在创建时可见
+    pub key: String,
+    pub record: ApiKeyRecord,
+}
+
+/// 拆分 `sk_<key_id>_<secret>` 格式的密钥
+pub fn parse_api_key(key: &str) -> FzResult<(&str, &str)> {
+    let malformed = || FzStreamError::auth(AuthErrorCode::MalformedToken, "invalid API key format");
+
+    let (key_id, secret) = key
+        .strip_prefix(API_KEY_PREFIX)
+        .and_then(|rest| rest.split_once('_'))
+        .ok_or_else(malformed)?;
+    let is_hex = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit());
+    if !is_hex(key_id) || secret.len() != API_KEY_SECRET_BYTES * 2 || !is_hex(secret) {
+        return Err(malformed());
+    }
+    Ok((key_id, secret))
+}
+
+/// API 密钥存储
+pub trait ApiKeyStore: Send + Sync {
+    fn get(&self, key_id: &str) -> FzResult<Option<ApiKeyRecord>>;
+
+    /// 添加或替换记录
+    fn put(&self, record: ApiKeyRecord) -> FzResult<()>;
+
+    fn remove(&self, key_id: &str) -> FzResult<Option<ApiKeyRecord>>;
+
+    /// 所有记录
+    fn list(&self) -> FzResult<Vec<ApiKeyRecord>>;
+}
+
+/// 内存 API 密钥存储
+#[derive(Debug, Default)]
+pub struct MemoryApiKeyStore {
+    records: RwLock<HashMap<String, ApiKeyRecord>>,
+}
+
+impl MemoryApiKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ApiKeyStore for MemoryApiKeyStore {
+    fn get(&self, key_id: &str) -> FzResult<Option<ApiKeyRecord>> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        Ok(records.get(key_id).cloned())
+    }
+
+    fn put(&self, record: ApiKeyRecord) -> FzResult<()> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        records.insert(record.key_id.clone(), record);
+        Ok(())
+    }
+
+    fn remove(&self, key_id: &str) -> FzResult<Option<ApiKeyRecord>> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        Ok(records.remove(key_id))
+    }
+
+    fn list(&self) -> FzResult<Vec<ApiKeyRecord>> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<ApiKeyRecord> = records.values().cloned().collect();
+        list.sort_by(|a, b| a.key_id.cmp(&b.key_id));
+        Ok(list)
+    }
+}
+
+/// API 密钥管理
+///
+/// 负责创建、验证、轮换和吊销 `sk_<key_id>_<secret>` 格式的密钥。
+#[derive(Clone)]
+pub struct ApiKeyManager {
+    store: Arc<dyn ApiKeyStore>,
+    rng: SystemRandom,
+}
+
+impl std::fmt::Debug for ApiKeyManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyManager").finish_non_exhaustive()
+    }
+}
+
+impl ApiKeyManager {
+    pub fn new(store: Arc<dyn ApiKeyStore>) -> Self {
+        Self {
+            store,
+            rng: SystemRandom::new(),
+        }
+    }
+
+    pub fn store(&self) -> &Arc<dyn ApiKeyStore> {
+        &self.store
+    }
+
+    /// 创建新密钥，`lifetime` 为 None 时永不过期
+    pub fn create(&self, name: impl Into<String>, permissions: Vec<String>, lifetime: Option<Duration>) -> FzResult<IssuedApiKey> {
+        let now = crate::unix_now().as_secs();
+        let key_id = loop {
+            let key_id = self.random_hex(API_KEY_ID_BYTES)?;
+            if self.store.get(&key_id)?.is_none() {
+                break key_id;
+            }
+        };
+        let secret = self.random_hex(API_KEY_SECRET_BYTES)?;
+
+        let record = ApiKeyRecord {
+            key_id: key_id.clone(),
+            name: name.into(),
+            secret_hash: hash_token(&secret),
+            permissions,
+            created_at: now,
+            expires_at: lifetime.map(|lifetime| now.saturating_add(lifetime.as_secs())),
+            replaced_by: None,
+        };
+        self.store.put(record.clone())?;
+
+        Ok(IssuedApiKey {
+            key: format!("{}{}_{}", API_KEY_PREFIX, key_id, secret),
+            record,
+        })
+    }
+
+    /// 验证密钥并返回记录
+    pub fn verify(&self, key: &str) -> FzResult<ApiKeyRecord> {
+        let (key_id, secret) = parse_api_key(key)?;
+        let record = self
+            .store
+            .get(key_id)?
+            .ok_or_else(|| FzStreamError::auth(AuthErrorCode::InvalidToken, "unknown API key"))?;
+
+        if !constant_time_eq(hash_token(secret).as_bytes(), record.secret_hash.as_bytes()) {
+            return Err(FzStreamError::auth(AuthErrorCode::InvalidToken, "invalid API key"));
+        }
+        if record.is_expired_at(crate::unix_now().as_secs()) {
+            return Err(FzStreamError::auth(AuthErrorCode::ExpiredToken, "API key expired"));
+        }
+        Ok(record)
+    }
+
+    /// 轮换密钥：创建权限和有效期相同的新密钥，旧密钥在 `grace` 后失效
+    pub fn rotate(&self, key_id: &str, grace: Duration) -> FzResult<IssuedApiKey> {
+        let mut old = self.get_record(key_id)?;
+        let now = crate::unix_now().as_secs();
+        let lifetime = old
+            .expires_at
+            .map(|expires_at| Duration::from_secs(expires_at.saturating_sub(old.created_at)));
+
+        let issued = self.create(old.name.clone(), old.permissions.clone(), lifetime)?;
+
+        let grace_end = now.saturating_add(grace.as_secs());
+        old.expires_at = Some(old.expires_at.map_or(grace_end, |expires_at| expires_at.min(grace_end)));
+        old.replaced_by = Some(issued.record.key_id.clone());
+        self.store.put(old)?;
+
+        Ok(issued)
+    }
+
+    /// 设置过期时间（Unix 秒）
+    pub fn set_expiry(&self, key_id: &str, expires_at: Option<u64>) -> FzResult<()> {
+        let mut record = self.get_record(key_id)?;
+        record.expires_at = expires_at;
+        self.store.put(record)
+    }
+
+    /// 替换密钥的权限
+    pub fn set_permissions(&self, key_id: &str, permissions: Vec<String>) -> FzResult<()> {
+        let mut record = self.get_record(key_id)?;
+        record.permissions = permissions;
+        self.store.put(record)
+    }
+
+    /// 吊销密钥
+    pub fn revoke(&self, key_id: &str) -> FzResult<bool> {
+        Ok(self.store.remove(key_id)?.is_some())
+    }
+
+    fn get_record(&self, key_id: &str) -> FzResult<ApiKeyRecord> {
+        self.store
+            .get(key_id)?
+            .ok_or_else(|| FzStreamError::Config(format!("unknown API key id: {}", key_id)))
+    }
+
+    fn random_hex(&self, len: usize) -> FzResult<String> {
+        let mut bytes = vec![0u8; len];
+        self.rng
+            .fill(&mut bytes)
+            .map_err(|_| FzStreamError::Config("system random generator failed".to_string()))?;
+        Ok(to_hex(&bytes))
+    }
+}
+
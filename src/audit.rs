@@ -0,0 +1,170 @@
+use serde::{Serialize, Deserialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{FzResult, FzStreamError};
+
+/// 认证所用的令牌类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    /// 凭据存储中的令牌
+    Credential,
+    ApiKey,
+    Session,
+    Jwt,
+    /// 无法识别的令牌
+    Unknown,
+}
+
+/// 认证结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthOutcome {
+    Success,
+    Failure,
+}
+
+/// 验证通过的认证主体
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditSubject {
+    /// 用户编号（JWT `sub`、会话用户）或凭据、API 密钥的名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// 凭据编号（JWT `jti`、会话编号、API 密钥编号）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+    /// JWT 签名密钥编号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+/// 认证审计事件（不包含令牌本身）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthAuditEvent {
+    /// 发生时间（Unix 毫秒）
+    pub timestamp: u64,
+    pub client_id: Option<String>,
+    pub token_kind: TokenKind,
+    pub outcome: AuthOutcome,
+    /// 失败时的错误码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+    /// 失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 成功时授予的权限
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<String>,
+    /// 成功时的认证主体（失败时令牌内容不可信，不记录）
+    #[serde(flatten)]
+    pub subject: AuditSubject,
+}
+
+impl AuthAuditEvent {
+    /// 根据验证结果构造事件，时间为当前时间
+    pub fn from_result(client_id: Option<&str>, token_kind: TokenKind, result: &FzResult<Vec<String>>) -> Self {
+        let timestamp = crate::unix_now().as_millis() as u64;
+
+        let (outcome, code, message, permissions) = match result {
+            Ok(permissions) => (AuthOutcome::Success, None, None, permissions.clone()),
+            Err(e) => (AuthOutcome::Failure, Some(e.code()), Some(e.to_string()), Vec::new()),
+        };
+        Self {
+            timestamp,
+            client_id: client_id.map(str::to_string),
+            token_kind,
+            outcome,
+            code,
+            message,
+            permissions,
+            subject: AuditSubject::default(),
+        }
+    }
+
+    pub fn with_subject(mut self, subject: AuditSubject) -> Self {
+        self.subject = subject;
+        self
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.outcome == AuthOutcome::Success
+    }
+}
+
+/// 审计事件输出
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuthAuditEvent) -> FzResult<()>;
+}
+
+/// 内存中的审计记录（用于测试或自定义转发）
+#[derive(Debug, Default)]
+pub struct MemoryAuditSink {
+    events: Mutex<Vec<AuthAuditEvent>>,
+}
+
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<AuthAuditEvent> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    fn record(&self, event: &AuthAuditEvent) -> FzResult<()> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).push(event.clone());
+        Ok(())
+    }
+}
+
+/// 以 JSON Lines 格式追加写入文件的审计记录，每个事件一行
+#[derive(Debug)]
+pub struct JsonLinesAuditSink {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl JsonLinesAuditSink {
+    /// 打开审计文件（不存在时创建，已存在时追加）
+    pub fn open(path: impl AsRef<Path>) -> FzResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| FzStreamError::Config(format!("failed to open audit log {}: {}", path.display(), e)))?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, event: &AuthAuditEvent) -> FzResult<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        // 整行一次写入，多个线程同时记录时不会交错
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line)
+            .and_then(|_| file.flush())
+            .map_err(|e| FzStreamError::Config(format!("failed to write audit log {}: {}", self.path.display(), e)))
+    }
+}
@@ -1,15 +1,87 @@
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::sync::Arc;
+use crate::api_key::ApiKeyManager;
+use crate::audit::{AuditSink, AuditSubject, AuthAuditEvent, TokenKind};
+use crate::credentials::{hash_token, CredentialStore};
 use crate::events::EventTypeFilter;
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+use crate::handshake::{Hello, Welcome};
+use crate::jwt::JwtVerifier;
+use crate::keyring::Keyring;
+use crate::nonce::AuthReplayGuard;
+use crate::permission::PermissionSet;
+use crate::quota::ClientQuota;
+use crate::revocation::RevocationList;
+use crate::session::SessionManager;
+use crate::ServerConfig;
 
 /// 认证消息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthMessage {
     pub auth_token: String,
     pub client_id: String,
+    /// 发送时间（Unix 毫秒），服务器据此拒绝过旧的消息
     pub timestamp: u64,
     /// 可选的事件类型过滤器
     pub event_filter: Option<EventTypeFilter>,
+    // Bincode 按字段顺序编码，以下新增字段只能追加在末尾，旧布局由 `from_bincode` 兼容
+    /// 每条认证消息唯一的随机值，用于检测重放
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// 客户端能力声明（旧客户端不携带，服务器使用默认组合）
+    #[serde(default)]
+    pub hello: Option<Hello>,
+}
+
+/// 不带 `nonce`、`hello` 的旧版认证消息布局
+#[derive(Deserialize)]
+struct AuthMessageV0 {
+    auth_token: String,
+    client_id: String,
+    timestamp: u64,
+    event_filter: Option<EventTypeFilter>,
+}
+
+impl From<AuthMessageV0> for AuthMessage {
+    fn from(v0: AuthMessageV0) -> Self {
+        Self {
+            auth_token: v0.auth_token,
+            client_id: v0.client_id,
+            timestamp: v0.timestamp,
+            event_filter: v0.event_filter,
+            nonce: None,
+            hello: None,
+        }
+    }
+}
+
+impl AuthMessage {
+    /// 以当前时间和随机 nonce 创建认证消息
+    pub fn new(auth_token: impl Into<String>, client_id: impl Into<String>, event_filter: Option<EventTypeFilter>) -> Self {
+        let timestamp = crate::unix_now().as_millis() as u64;
+
+        Self {
+            auth_token: auth_token.into(),
+            client_id: client_id.into(),
+            timestamp,
+            event_filter,
+            nonce: Some(uuid::Uuid::new_v4().simple().to_string()),
+            hello: None,
+        }
+    }
+
+    /// 解码 Bincode 认证消息，兼容不带 `nonce`、`hello` 的旧客户端
+    pub fn from_bincode(bytes: &[u8]) -> FzResult<Self> {
+        match bincode::deserialize::<Self>(bytes) {
+            Ok(message) => Ok(message),
+            Err(e) => bincode::deserialize::<AuthMessageV0>(bytes).map(Self::from).map_err(|_| e.into()),
+        }
+    }
+
+    pub fn with_hello(mut self, hello: Hello) -> Self {
+        self.hello = Some(hello);
+        self
+    }
 }
 
 /// 认证响应
@@ -21,6 +93,12 @@ pub enum AuthResponse {
         permissions: Vec<String>,
         /// 服务器确认的事件过滤器设置
         event_filter: Option<EventTypeFilter>,
+        /// 协商结果（客户端未携带 `hello` 时为 None）
+        #[serde(default)]
+        welcome: Option<Welcome>,
+        /// 令牌携带的配额（None 表示不限制）
+        #[serde(default)]
+        quota: Option<ClientQuota>,
     },
     Failure {
         error: String,
@@ -28,6 +106,35 @@ pub enum AuthResponse {
     },
 }
 
+impl AuthResponse {
+    /// 根据令牌权限构造成功响应，`event_filter` 为请求的过滤器与权限的交集
+    pub fn authorized(
+        client_id: impl Into<String>,
+        permissions: Vec<String>,
+        requested_filter: Option<&EventTypeFilter>,
+        welcome: Option<Welcome>,
+        quota: Option<ClientQuota>,
+    ) -> FzResult<Self> {
+        let event_filter = PermissionSet::from_strings(&permissions).authorize_filter(requested_filter)?;
+        Ok(AuthResponse::Success {
+            message: "authenticated".to_string(),
+            client_id: client_id.into(),
+            permissions,
+            event_filter: Some(event_filter),
+            welcome,
+            quota,
+        })
+    }
+
+    /// 根据错误构造失败响应，`code` 使用错误的稳定数值码
+    pub fn failure(error: &FzStreamError) -> Self {
+        AuthResponse::Failure {
+            error: error.to_string(),
+            code: error.code(),
+        }
+    }
+}
+
 /// 令牌声明
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenClaims {
@@ -36,106 +143,328 @@ pub struct TokenClaims {
     pub issued_at: u64,
     pub expires_at: u64,
     pub client_info: Option<String>,
+    /// 客户端配额
+    #[serde(default)]
+    pub quota: Option<ClientQuota>,
+    /// 令牌编号（JWT `jti`），用于吊销
+    #[serde(default)]
+    pub token_id: Option<String>,
+    /// 验证通过的签名密钥编号（验证器中配置的 `kid`，不是令牌头部声明的值）
+    #[serde(default)]
+    pub key_id: Option<String>,
 }
 
 /// 认证令牌验证器
+///
+/// 先按令牌哈希查找凭据存储，未命中时再按令牌格式验证。
 pub struct AuthTokenValidator {
-    predefined_tokens: HashMap<String, Vec<String>>,
+    credential_stores: Vec<Arc<dyn CredentialStore>>,
+    api_keys: Option<ApiKeyManager>,
+    sessions: Option<Arc<SessionManager>>,
+    jwt_verifier: Option<JwtVerifier>,
+    keyring: Option<Arc<Keyring>>,
+    revocations: Option<Arc<RevocationList>>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    replay_guard: Option<AuthReplayGuard>,
+}
+
+impl Default for AuthTokenValidator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AuthTokenValidator {
     /// 创建新的认证令牌验证器
+    ///
+    /// 启用 `dev-tokens` 特性时预置演示令牌，生产环境不应启用。
     pub fn new() -> Self {
-        let mut predefined_tokens = HashMap::new();
-        
-        // 添加预定义的认证令牌
-        predefined_tokens.insert(
-            "demo_token_12345".to_string(),
-            vec!["read".to_string(), "stream".to_string()]
-        );
-        predefined_tokens.insert(
-            "test_auth_token".to_string(),
-            vec!["read".to_string(), "stream".to_string(), "write".to_string()]
-        );
-        predefined_tokens.insert(
-            "admin_super_token_999".to_string(),
-            vec!["read".to_string(), "stream".to_string(), "write".to_string(), "admin".to_string()]
-        );
-        
         Self {
-            predefined_tokens,
+            credential_stores: Vec::new(),
+            api_keys: None,
+            sessions: None,
+            jwt_verifier: None,
+            keyring: None,
+            revocations: None,
+            audit_sink: None,
+            replay_guard: None,
+        }
+        .with_dev_tokens()
+    }
+
+    #[cfg(feature = "dev-tokens")]
+    fn with_dev_tokens(self) -> Self {
+        self.with_credential_store(Arc::new(crate::credentials::MemoryCredentialStore::with_dev_tokens()))
+    }
+
+    #[cfg(not(feature = "dev-tokens"))]
+    fn with_dev_tokens(self) -> Self {
+        self
+    }
+
+    /// 添加凭据存储（按添加顺序查找）
+    pub fn with_credential_store(mut self, store: Arc<dyn CredentialStore>) -> Self {
+        self.add_credential_store(store);
+        self
+    }
+
+    pub fn add_credential_store(&mut self, store: Arc<dyn CredentialStore>) {
+        self.credential_stores.push(store);
+    }
+
+    /// 根据服务器配置创建验证器
+    ///
+    /// 配置了 `auth_secret_key` 时启用 HS256 JWT 和会话令牌验证，配置了 `signing_keys` 时用密钥环验证 JWT。
+    pub fn from_server_config(config: &ServerConfig) -> Self {
+        let mut validator = Self::new();
+        if let Some(keyring) = Keyring::from_server_config(config) {
+            validator = validator.with_keyring(Arc::new(keyring));
+        }
+        match (&config.auth_secret_key, SessionManager::from_server_config(config)) {
+            (Some(secret), Some(sessions)) => validator
+                .with_jwt_verifier(JwtVerifier::hs256(secret.as_bytes()))
+                .with_sessions(Arc::new(sessions)),
+            _ => validator,
+        }
+    }
+
+    /// 设置 API 密钥管理，未设置时拒绝所有 `sk_` 令牌
+    pub fn with_api_keys(mut self, api_keys: ApiKeyManager) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
+    pub fn api_keys(&self) -> Option<&ApiKeyManager> {
+        self.api_keys.as_ref()
+    }
+
+    /// 设置会话管理，未设置时拒绝所有 `sess_` 令牌
+    pub fn with_sessions(mut self, sessions: Arc<SessionManager>) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    pub fn sessions(&self) -> Option<&Arc<SessionManager>> {
+        self.sessions.as_ref()
+    }
+
+    /// 设置 JWT 验证器
+    pub fn with_jwt_verifier(mut self, verifier: JwtVerifier) -> Self {
+        self.jwt_verifier = Some(verifier);
+        self
+    }
+
+    pub fn jwt_verifier(&self) -> Option<&JwtVerifier> {
+        self.jwt_verifier.as_ref()
+    }
+
+    /// 设置签名密钥环，设置后优先于 JWT 验证器
+    pub fn with_keyring(mut self, keyring: Arc<Keyring>) -> Self {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    pub fn keyring(&self) -> Option<&Arc<Keyring>> {
+        self.keyring.as_ref()
+    }
+
+    /// 设置吊销列表，验证通过的令牌还需不在列表中
+    pub fn with_revocation_list(mut self, revocations: Arc<RevocationList>) -> Self {
+        self.revocations = Some(revocations);
+        self
+    }
+
+    pub fn revocation_list(&self) -> Option<&Arc<RevocationList>> {
+        self.revocations.as_ref()
+    }
+
+    /// 设置审计输出，每次验证令牌后记录一条事件（写入失败不影响认证结果）
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// 设置重放检查，`authenticate` 会先检查消息的时间戳和 nonce 再验证令牌
+    pub fn with_replay_guard(mut self, guard: AuthReplayGuard) -> Self {
+        self.replay_guard = Some(guard);
+        self
+    }
+
+    pub fn replay_guard(&self) -> Option<&AuthReplayGuard> {
+        self.replay_guard.as_ref()
+    }
+
+    /// 验证 JWT 并返回完整声明（同样记录审计事件）
+    pub fn validate_jwt(&self, token: &str) -> FzResult<TokenClaims> {
+        let result = self.verify_jwt(token);
+        let verified = result.as_ref().map(Verified::from).map_err(Clone::clone);
+        self.record_audit(None, TokenKind::Jwt, &verified);
+        result
+    }
+
+    fn verify_jwt(&self, token: &str) -> FzResult<TokenClaims> {
+        let claims = match (&self.keyring, &self.jwt_verifier) {
+            (Some(keyring), _) => keyring.verify(token)?,
+            (None, Some(verifier)) => verifier.verify(token)?,
+            (None, None) => {
+                return Err(FzStreamError::auth(AuthErrorCode::InvalidToken, "JWT validation is not configured"));
+            }
+        };
+        self.check_revoked(claims.token_id.as_deref(), Some(&claims.user_id), claims.key_id.as_deref())?;
+        Ok(claims)
+    }
+
+    /// 查询吊销列表，未设置时不做检查
+    fn check_revoked(&self, token_id: Option<&str>, user_id: Option<&str>, key_id: Option<&str>) -> FzResult<()> {
+        match &self.revocations {
+            Some(revocations) => revocations.check(token_id, user_id, key_id),
+            None => Ok(()),
         }
     }
     
     /// 验证认证令牌
-    pub fn validate_token(&self, token: &str) -> Result<Vec<String>, String> {
-        // 检查预定义令牌
-        if let Some(permissions) = self.predefined_tokens.get(token) {
-            return Ok(permissions.clone());
+    ///
+    /// 审计事件不含客户端编号（已知时使用 `authenticate`），但会记录验证通过的主体。
+    pub fn validate_token(&self, token: &str) -> FzResult<Vec<String>> {
+        self.validate_and_audit(token, None)
+    }
+
+    /// 验证认证消息中的令牌，审计事件会记录客户端编号
+    ///
+    /// 设置了重放检查时先检查时间戳和 nonce，未通过时不验证令牌（审计事件的令牌类型为 `Unknown`）。
+    pub fn authenticate(&self, message: &AuthMessage) -> FzResult<Vec<String>> {
+        if let Some(guard) = &self.replay_guard {
+            if let Err(e) = guard.check(message) {
+                self.record_audit(Some(&message.client_id), TokenKind::Unknown, &Err(e.clone()));
+                return Err(e);
+            }
         }
-        
-        // 检查JWT格式令牌
-        if token.contains('.') {
-            return self.validate_jwt_token(token);
+        self.validate_and_audit(&message.auth_token, Some(&message.client_id))
+    }
+
+    fn validate_and_audit(&self, token: &str, client_id: Option<&str>) -> FzResult<Vec<String>> {
+        let (token_kind, result) = self.validate_token_kind(token);
+        self.record_audit(client_id, token_kind, &result);
+        result.map(|verified| verified.permissions)
+    }
+
+    fn record_audit(&self, client_id: Option<&str>, token_kind: TokenKind, result: &FzResult<Verified>) {
+        if let Some(sink) = &self.audit_sink {
+            let permissions = result.as_ref().map(|verified| verified.permissions.clone()).map_err(Clone::clone);
+            let subject = result.as_ref().map(|verified| verified.subject.clone()).unwrap_or_default();
+            let _ = sink.record(&AuthAuditEvent::from_result(client_id, token_kind, &permissions).with_subject(subject));
+        }
+    }
+
+    fn validate_token_kind(&self, token: &str) -> (TokenKind, FzResult<Verified>) {
+        // 检查凭据存储
+        match self.lookup_credential(token) {
+            Ok(Some(verified)) => return (TokenKind::Credential, Ok(verified)),
+            Ok(None) => {}
+            Err(e) => return (TokenKind::Credential, Err(e)),
         }
         
         // 检查API密钥格式
         if token.starts_with("sk_") {
-            return self.validate_api_key(token);
+            return (TokenKind::ApiKey, self.validate_api_key(token));
         }
         
         // 检查会话令牌格式
         if token.starts_with("sess_") {
-            return self.validate_session_token(token);
+            return (TokenKind::Session, self.validate_session_token(token));
         }
         
-        Err("无效的认证令牌".to_string())
+        // 检查JWT格式令牌
+        if token.contains('.') {
+            return (TokenKind::Jwt, self.validate_jwt_token(token));
+        }
+        
+        (TokenKind::Unknown, Err(FzStreamError::auth(AuthErrorCode::InvalidToken, "unrecognized token")))
     }
     
-    /// 验证JWT令牌
-    fn validate_jwt_token(&self, token: &str) -> Result<Vec<String>, String> {
-        // 这里应该实现JWT验证逻辑
-        // 为了简化，我们只做基本检查
-        if token.split('.').count() == 3 {
-            Ok(vec!["read".to_string(), "stream".to_string()])
-        } else {
-            Err("无效的JWT令牌格式".to_string())
+    /// 在凭据存储中查找令牌
+    fn lookup_credential(&self, token: &str) -> FzResult<Option<Verified>> {
+        if self.credential_stores.is_empty() {
+            return Ok(None);
         }
+
+        let token_hash = hash_token(token);
+        for store in &self.credential_stores {
+            if let Some(record) = store.lookup(&token_hash)? {
+                let now = crate::unix_now().as_secs();
+                if record.is_expired_at(now) {
+                    return Err(FzStreamError::auth(AuthErrorCode::ExpiredToken, "credential expired"));
+                }
+                self.check_revoked(None, Some(&record.name), None)?;
+                return Ok(Some(Verified {
+                    subject: AuditSubject { subject: Some(record.name), ..AuditSubject::default() },
+                    permissions: record.permissions,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 验证JWT令牌
+    fn validate_jwt_token(&self, token: &str) -> FzResult<Verified> {
+        self.verify_jwt(token).map(|claims| Verified::from(&claims))
     }
     
     /// 验证API密钥
-    fn validate_api_key(&self, token: &str) -> Result<Vec<String>, String> {
-        // 检查API密钥格式: sk_userid_timestamp_random
-        let parts: Vec<&str> = token.split('_').collect();
-        if parts.len() >= 3 && parts[0] == "sk" {
-            Ok(vec!["read".to_string(), "stream".to_string()])
-        } else {
-            Err("无效的API密钥格式".to_string())
+    fn validate_api_key(&self, token: &str) -> FzResult<Verified> {
+        let record = self
+            .api_keys
+            .as_ref()
+            .ok_or_else(|| FzStreamError::auth(AuthErrorCode::InvalidToken, "API key validation is not configured"))?
+            .verify(token)?;
+        if let Some(revocations) = &self.revocations {
+            revocations.check_api_key(&record.key_id)?;
         }
+        self.check_revoked(None, Some(&record.name), None)?;
+        Ok(Verified {
+            subject: AuditSubject {
+                subject: Some(record.name),
+                credential_id: Some(record.key_id),
+                key_id: None,
+            },
+            permissions: record.permissions,
+        })
     }
     
     /// 验证会话令牌
-    fn validate_session_token(&self, token: &str) -> Result<Vec<String>, String> {
-        // 检查会话令牌格式: sess_timestamp_userid_random
-        let parts: Vec<&str> = token.split('_').collect();
-        if parts.len() >= 4 && parts[0] == "sess" {
-            // 检查令牌是否过期（1小时）
-            if let Ok(timestamp) = parts[1].parse::<u64>() {
-                let current_time = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                
-                if current_time - timestamp < 3600 {
-                    Ok(vec!["read".to_string(), "stream".to_string()])
-                } else {
-                    Err("会话令牌已过期".to_string())
-                }
-            } else {
-                Err("无效的会话令牌时间戳".to_string())
-            }
-        } else {
-            Err("无效的会话令牌格式".to_string())
+    fn validate_session_token(&self, token: &str) -> FzResult<Verified> {
+        let claims = self
+            .sessions
+            .as_ref()
+            .ok_or_else(|| FzStreamError::auth(AuthErrorCode::InvalidToken, "session validation is not configured"))?
+            .verify(token)?;
+        self.check_revoked(Some(&claims.session_id), Some(&claims.user_id), None)?;
+        Ok(Verified {
+            subject: AuditSubject {
+                subject: Some(claims.user_id),
+                credential_id: Some(claims.session_id),
+                key_id: None,
+            },
+            permissions: claims.permissions,
+        })
+    }
+}
+
+/// 验证通过的令牌
+struct Verified {
+    permissions: Vec<String>,
+    subject: AuditSubject,
+}
+
+impl From<&TokenClaims> for Verified {
+    fn from(claims: &TokenClaims) -> Self {
+        Self {
+            permissions: claims.permissions.clone(),
+            subject: AuditSubject {
+                subject: Some(claims.user_id.clone()),
+                credential_id: claims.token_id.clone(),
+                key_id: claims.key_id.clone(),
+            },
         }
     }
 }
@@ -0,0 +1,337 @@
+use serde::{Serialize, Deserialize};
+
+use crate::compression::{compress_data, decompress_data_with_limit, DEFAULT_MAX_DECOMPRESSED_SIZE};
+use crate::dictionary::DictionaryStore;
+use crate::error::{FzResult, FzStreamError};
+use crate::events::EventType;
+use crate::wire::{FrameError, FrameKind, WireMessage, FLAG_COMPRESSED, FLAG_DICTIONARY};
+use crate::{CompressionLevel, CompressionType, SerializationProtocol};
+
+/// 批量事件消息
+///
+/// 将同一 slot 内的多个事件（共享序列化协议和压缩方式）合并到一个数据块中压缩，
+/// 通过偏移量定位每个事件，减少单条消息的头部开销并提升压缩率。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBatch {
+    pub slot: u64,
+    pub timestamp: u64,
+    pub serialization_format: SerializationProtocol,
+    pub compression_format: CompressionLevel,
+    pub is_compressed: bool,
+    pub original_size: Option<usize>, // 压缩前数据块大小
+    pub dictionary_id: Option<u32>,
+    pub event_types: Vec<EventType>,
+    pub offsets: Vec<u32>, // 每个事件在未压缩数据块中的起始偏移
+    pub data: Vec<u8>,     // 所有事件数据拼接后（可能压缩）的数据块
+    // Bincode 按字段顺序编码，以下字段为次版本 1 追加，新字段只能继续追加在末尾
+    #[serde(default)]
+    pub first_sequence: u64, // 第一个事件的序列号，后续事件依次递增（0 表示未分配）
+}
+
+/// 次版本 0 的 Bincode 布局（没有次版本 1 追加的字段）
+#[derive(Deserialize)]
+struct EventBatchV0 {
+    slot: u64,
+    timestamp: u64,
+    serialization_format: SerializationProtocol,
+    compression_format: CompressionLevel,
+    is_compressed: bool,
+    original_size: Option<usize>,
+    dictionary_id: Option<u32>,
+    event_types: Vec<EventType>,
+    offsets: Vec<u32>,
+    data: Vec<u8>,
+}
+
+impl From<EventBatchV0> for EventBatch {
+    fn from(v0: EventBatchV0) -> Self {
+        Self {
+            slot: v0.slot,
+            timestamp: v0.timestamp,
+            serialization_format: v0.serialization_format,
+            compression_format: v0.compression_format,
+            is_compressed: v0.is_compressed,
+            original_size: v0.original_size,
+            dictionary_id: v0.dictionary_id,
+            event_types: v0.event_types,
+            offsets: v0.offsets,
+            data: v0.data,
+            first_sequence: 0,
+        }
+    }
+}
+
+impl EventBatch {
+    /// 批量中的事件数量
+    pub fn len(&self) -> usize {
+        self.event_types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.event_types.is_empty()
+    }
+
+    /// 最后一个事件的序列号（未分配序列号或批量为空时返回 None）
+    pub fn last_sequence(&self) -> Option<u64> {
+        if self.first_sequence == 0 || self.is_empty() {
+            None
+        } else {
+            Some(self.first_sequence + self.len() as u64 - 1)
+        }
+    }
+
+    /// 解压数据块，返回可迭代的批量事件
+    pub fn decode(&self) -> FzResult<DecodedBatch<'_>> {
+        self.decode_with_limit(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// 解压数据块，解压后超过 `max_size` 字节时返回错误
+    pub fn decode_with_limit(&self, max_size: usize) -> FzResult<DecodedBatch<'_>> {
+        if let (true, Some(dictionary_id)) = (self.is_compressed, self.dictionary_id) {
+            return Err(FzStreamError::Decompression(format!(
+                "batch was compressed with dictionary {}, use decode_with_dictionaries",
+                dictionary_id
+            )));
+        }
+
+        let block = if self.is_compressed {
+            decompress_data_with_limit(&self.data, self.compression_format, max_size)?
+        } else {
+            self.data.clone()
+        };
+        DecodedBatch::new(self, block)
+    }
+
+    /// 解压数据块，批量使用字典压缩时从 `dictionaries` 中查找字典
+    pub fn decode_with_dictionaries(&self, dictionaries: &DictionaryStore) -> FzResult<DecodedBatch<'_>> {
+        match self.dictionary_id {
+            Some(dictionary_id) if self.is_compressed => {
+                let block = dictionaries.decompress(dictionary_id, &self.data, DEFAULT_MAX_DECOMPRESSED_SIZE)?;
+                DecodedBatch::new(self, block)
+            }
+            _ => self.decode(),
+        }
+    }
+}
+
+impl WireMessage for EventBatch {
+    const KIND: FrameKind = FrameKind::Batch;
+
+    fn serialization_format(&self) -> SerializationProtocol {
+        self.serialization_format
+    }
+
+    fn codec_id(&self) -> u8 {
+        CompressionType::from(self.compression_format).wire_id()
+    }
+
+    fn data_flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.is_compressed {
+            flags |= FLAG_COMPRESSED;
+        }
+        if self.dictionary_id.is_some() {
+            flags |= FLAG_DICTIONARY;
+        }
+        flags
+    }
+
+    fn from_legacy_bincode(payload: &[u8], version_minor: u8) -> Option<Result<Self, FrameError>> {
+        (version_minor == 0).then(|| {
+            bincode::deserialize::<EventBatchV0>(payload)
+                .map(Self::from)
+                .map_err(|e| FrameError::Serialization(e.to_string()))
+        })
+    }
+}
+
+/// 批量中的单个事件
+#[derive(Debug, Clone, Copy)]
+pub struct BatchEvent<'a> {
+    pub index: usize,
+    pub sequence: u64, // 未分配序列号时为 0
+    pub event_type: &'a EventType,
+    pub data: &'a [u8],
+}
+
+/// 已解压的批量事件
+#[derive(Debug)]
+pub struct DecodedBatch<'a> {
+    batch: &'a EventBatch,
+    block: Vec<u8>,
+}
+
+impl<'a> DecodedBatch<'a> {
+    fn new(batch: &'a EventBatch, block: Vec<u8>) -> FzResult<Self> {
+        if batch.offsets.len() != batch.event_types.len() {
+            return Err(FzStreamError::Serialization(format!(
+                "batch has {} offsets for {} events",
+                batch.offsets.len(),
+                batch.event_types.len()
+            )));
+        }
+        if let Some(original_size) = batch.original_size {
+            if block.len() != original_size {
+                return Err(FzStreamError::Decompression(format!(
+                    "decompressed {} bytes, expected {}",
+                    block.len(),
+                    original_size
+                )));
+            }
+        }
+
+        let mut previous = 0;
+        for &offset in &batch.offsets {
+            if offset < previous || offset as usize > block.len() {
+                return Err(FzStreamError::Serialization(format!("invalid batch offset {}", offset)));
+            }
+            previous = offset;
+        }
+
+        Ok(Self { batch, block })
+    }
+
+    pub fn len(&self) -> usize {
+        self.batch.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batch.is_empty()
+    }
+
+    /// 获取第 `index` 个事件
+    pub fn get(&self, index: usize) -> Option<BatchEvent<'_>> {
+        let start = *self.batch.offsets.get(index)? as usize;
+        let end = self
+            .batch
+            .offsets
+            .get(index + 1)
+            .map(|&offset| offset as usize)
+            .unwrap_or(self.block.len());
+
+        let sequence = if self.batch.first_sequence == 0 {
+            0
+        } else {
+            self.batch.first_sequence + index as u64
+        };
+
+        Some(BatchEvent {
+            index,
+            sequence,
+            event_type: &self.batch.event_types[index],
+            data: &self.block[start..end],
+        })
+    }
+
+    /// 按顺序迭代批量中的事件
+    pub fn iter(&self) -> impl Iterator<Item = BatchEvent<'_>> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+}
+
+/// 批量事件构建器
+#[derive(Debug)]
+pub struct EventBatchBuilder {
+    slot: u64,
+    first_sequence: u64,
+    serialization_format: SerializationProtocol,
+    compression_format: CompressionLevel,
+    event_types: Vec<EventType>,
+    offsets: Vec<u32>,
+    block: Vec<u8>,
+}
+
+impl EventBatchBuilder {
+    pub fn new(slot: u64, serialization_format: SerializationProtocol, compression_format: CompressionLevel) -> Self {
+        Self {
+            slot,
+            first_sequence: 0,
+            serialization_format,
+            compression_format,
+            event_types: Vec::new(),
+            offsets: Vec::new(),
+            block: Vec::new(),
+        }
+    }
+
+    /// 添加一个已序列化的事件
+    pub fn push(&mut self, event_type: EventType, data: &[u8]) -> FzResult<()> {
+        let offset = u32::try_from(self.block.len())
+            .map_err(|_| FzStreamError::Serialization("batch data block exceeds 4 GiB".to_string()))?;
+
+        self.event_types.push(event_type);
+        self.offsets.push(offset);
+        self.block.extend_from_slice(data);
+        Ok(())
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// 设置第一个事件的序列号（通常来自 `SequenceGenerator::next_range`）
+    pub fn set_first_sequence(&mut self, first_sequence: u64) {
+        self.first_sequence = first_sequence;
+    }
+
+    pub fn len(&self) -> usize {
+        self.event_types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.event_types.is_empty()
+    }
+
+    /// 当前未压缩数据块的大小
+    pub fn data_size(&self) -> usize {
+        self.block.len()
+    }
+
+    /// 构建批量消息，压缩失败或压缩后不更小时保留原始数据块
+    pub fn build(self) -> EventBatch {
+        let compressed = if self.compression_format != CompressionLevel::None {
+            compress_data(&self.block, self.compression_format).ok()
+        } else {
+            None
+        };
+        self.finish(compressed, None)
+    }
+
+    /// 使用 zstd 字典构建批量消息，非 zstd 压缩级别等同于 `build`
+    pub fn build_with_dictionary(self, dictionaries: &DictionaryStore, dictionary_id: u32) -> EventBatch {
+        if CompressionType::from(self.compression_format) != CompressionType::Zstd {
+            return self.build();
+        }
+
+        let compressed = dictionaries
+            .compress(dictionary_id, &self.block, self.compression_format.codec_level())
+            .ok();
+        self.finish(compressed, Some(dictionary_id))
+    }
+
+    fn finish(self, compressed: Option<Vec<u8>>, dictionary_id: Option<u32>) -> EventBatch {
+        let timestamp = crate::unix_now().as_millis() as u64;
+
+        let original_size = self.block.len();
+        let (data, original_size, is_compressed, dictionary_id) = match compressed {
+            Some(compressed_data) if compressed_data.len() < original_size => {
+                (compressed_data, Some(original_size), true, dictionary_id)
+            }
+            _ => (self.block, None, false, None),
+        };
+
+        EventBatch {
+            slot: self.slot,
+            timestamp,
+            serialization_format: self.serialization_format,
+            compression_format: self.compression_format,
+            is_compressed,
+            original_size,
+            dictionary_id,
+            event_types: self.event_types,
+            offsets: self.offsets,
+            data,
+            first_sequence: self.first_sequence,
+        }
+    }
+}
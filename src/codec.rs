@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, OnceLock};
+
+use crate::error::{FzResult, FzStreamError};
+
+/// 不压缩（直通）编解码器编号
+pub const CODEC_ID_NONE: u8 = 0;
+/// LZ4 编解码器编号
+pub const CODEC_ID_LZ4: u8 = 1;
+/// zstd 编解码器编号
+pub const CODEC_ID_ZSTD: u8 = 2;
+
+/// 压缩编解码器
+///
+/// `id` 即帧头中的 `codec_id`，同一注册表内必须唯一。
+/// 自定义编解码器（如 Snappy）应使用 128 及以上的编号，避免与内置编号冲突。
+pub trait Codec: Send + Sync {
+    /// 编解码器编号
+    fn id(&self) -> u8;
+
+    /// 编解码器名称（用于日志和统计）
+    fn name(&self) -> &str;
+
+    /// 压缩 `input` 并追加到 `output`，`level` 的含义由编解码器自行定义
+    fn compress_into(&self, input: &[u8], level: i32, output: &mut Vec<u8>) -> FzResult<()>;
+
+    /// 解压 `input` 并追加到 `output`，解压后超过 `max_size` 字节时返回错误
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>, max_size: usize) -> FzResult<()>;
+
+    /// 压缩 `input_len` 字节时输出的最大长度
+    fn max_compressed_len(&self, input_len: usize) -> usize;
+}
+
+/// 直通编解码器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoneCodec;
+
+impl Codec for NoneCodec {
+    fn id(&self) -> u8 {
+        CODEC_ID_NONE
+    }
+
+    fn name(&self) -> &str {
+        "none"
+    }
+
+    fn compress_into(&self, input: &[u8], _level: i32, output: &mut Vec<u8>) -> FzResult<()> {
+        output.extend_from_slice(input);
+        Ok(())
+    }
+
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>, max_size: usize) -> FzResult<()> {
+        check_decompressed_size(input.len(), max_size)?;
+        output.extend_from_slice(input);
+        Ok(())
+    }
+
+    fn max_compressed_len(&self, input_len: usize) -> usize {
+        input_len
+    }
+}
+
+/// LZ4 block 编解码器
+///
+/// 输出开头携带 4 字节小端原始大小；`level > 0` 时使用高压缩模式。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lz4Codec;
+
+/// LZ4 输出前置的原始大小字段长度（小端 u32）
+const LZ4_SIZE_PREFIX_LEN: usize = 4;
+
+impl Codec for Lz4Codec {
+    fn id(&self) -> u8 {
+        CODEC_ID_LZ4
+    }
+
+    fn name(&self) -> &str {
+        "lz4"
+    }
+
+    fn compress_into(&self, input: &[u8], level: i32, output: &mut Vec<u8>) -> FzResult<()> {
+        let mode = if level > 0 {
+            Some(lz4::block::CompressionMode::HIGHCOMPRESSION(level))
+        } else {
+            None
+        };
+
+        let start = output.len();
+        output.resize(start + self.max_compressed_len(input.len()), 0);
+        let written = lz4::block::compress_to_buffer(input, mode, true, &mut output[start..])
+            .map_err(|e| {
+                output.truncate(start);
+                FzStreamError::Compression(format!("lz4: {}", e))
+            })?;
+        output.truncate(start + written);
+        Ok(())
+    }
+
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>, max_size: usize) -> FzResult<()> {
+        if input.len() < LZ4_SIZE_PREFIX_LEN {
+            return Err(FzStreamError::Decompression("lz4: missing size prefix".to_string()));
+        }
+        let size = u32::from_le_bytes([input[0], input[1], input[2], input[3]]) as usize;
+        if size > i32::MAX as usize {
+            return Err(FzStreamError::Decompression(format!("lz4: invalid size prefix {}", size)));
+        }
+        check_decompressed_size(size, max_size)?;
+
+        let start = output.len();
+        output.resize(start + size, 0);
+        let written = lz4::block::decompress_to_buffer(input, None, &mut output[start..])
+            .map_err(|e| {
+                output.truncate(start);
+                FzStreamError::Decompression(format!("lz4: {}", e))
+            })?;
+        output.truncate(start + written);
+        Ok(())
+    }
+
+    fn max_compressed_len(&self, input_len: usize) -> usize {
+        lz4::block::compress_bound(input_len).unwrap_or(input_len + input_len / 255 + 16) + LZ4_SIZE_PREFIX_LEN
+    }
+}
+
+/// zstd 编解码器，`level` 即 zstd 压缩级别
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZstdCodec;
+
+impl Codec for ZstdCodec {
+    fn id(&self) -> u8 {
+        CODEC_ID_ZSTD
+    }
+
+    fn name(&self) -> &str {
+        "zstd"
+    }
+
+    fn compress_into(&self, input: &[u8], level: i32, output: &mut Vec<u8>) -> FzResult<()> {
+        zstd::stream::copy_encode(input, &mut *output, level)
+            .map_err(|e| FzStreamError::Compression(format!("zstd: {}", e)))
+    }
+
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>, max_size: usize) -> FzResult<()> {
+        let decoder = zstd::stream::read::Decoder::new(input)
+            .map_err(|e| FzStreamError::Decompression(format!("zstd: {}", e)))?;
+
+        let start = output.len();
+        // 多读一个字节以判断是否超过上限
+        decoder
+            .take(max_size as u64 + 1)
+            .read_to_end(output)
+            .map_err(|e| FzStreamError::Decompression(format!("zstd: {}", e)))?;
+        check_decompressed_size(output.len() - start, max_size)
+    }
+
+    fn max_compressed_len(&self, input_len: usize) -> usize {
+        zstd::zstd_safe::compress_bound(input_len)
+    }
+}
+
+pub(crate) fn check_decompressed_size(size: usize, max_size: usize) -> FzResult<()> {
+    if size > max_size {
+        return Err(FzStreamError::Decompression(format!(
+            "decompressed size {} exceeds limit of {} bytes",
+            size, max_size
+        )));
+    }
+    Ok(())
+}
+
+/// 编解码器注册表，按编号查找编解码器
+#[derive(Clone)]
+pub struct CodecRegistry {
+    codecs: HashMap<u8, Arc<dyn Codec>>,
+}
+
+impl Default for CodecRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl std::fmt::Debug for CodecRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ids = self.ids();
+        ids.sort_unstable();
+        f.debug_struct("CodecRegistry").field("ids", &ids).finish()
+    }
+}
+
+impl CodecRegistry {
+    /// 创建空注册表
+    pub fn new() -> Self {
+        Self {
+            codecs: HashMap::new(),
+        }
+    }
+
+    /// 创建已注册内置编解码器（none、lz4、zstd）的注册表
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(NoneCodec));
+        registry.register(Arc::new(Lz4Codec));
+        registry.register(Arc::new(ZstdCodec));
+        registry
+    }
+
+    /// 进程内共享的默认注册表（只包含内置编解码器）
+    pub fn global() -> &'static CodecRegistry {
+        static GLOBAL: OnceLock<CodecRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(CodecRegistry::with_defaults)
+    }
+
+    /// 注册编解码器，返回被替换的同编号编解码器
+    pub fn register(&mut self, codec: Arc<dyn Codec>) -> Option<Arc<dyn Codec>> {
+        self.codecs.insert(codec.id(), codec)
+    }
+
+    pub fn get(&self, id: u8) -> Option<&Arc<dyn Codec>> {
+        self.codecs.get(&id)
+    }
+
+    pub fn contains(&self, id: u8) -> bool {
+        self.codecs.contains_key(&id)
+    }
+
+    /// 已注册的编解码器编号
+    pub fn ids(&self) -> Vec<u8> {
+        self.codecs.keys().copied().collect()
+    }
+
+    /// 使用指定编解码器压缩
+    pub fn compress(&self, id: u8, input: &[u8], level: i32) -> FzResult<Vec<u8>> {
+        let codec = self
+            .get(id)
+            .ok_or_else(|| FzStreamError::Compression(format!("unknown codec id: {}", id)))?;
+
+        let mut output = Vec::with_capacity(codec.max_compressed_len(input.len()));
+        codec.compress_into(input, level, &mut output)?;
+        Ok(output)
+    }
+
+    /// 使用指定编解码器解压，解压后超过 `max_size` 字节时返回错误
+    pub fn decompress(&self, id: u8, input: &[u8], max_size: usize) -> FzResult<Vec<u8>> {
+        let codec = self
+            .get(id)
+            .ok_or_else(|| FzStreamError::Decompression(format!("unknown codec id: {}", id)))?;
+
+        let mut output = Vec::new();
+        codec.decompress_into(input, &mut output, max_size)?;
+        Ok(output)
+    }
+}
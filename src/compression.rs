@@ -1,5 +1,7 @@
 use serde::{Serialize, Deserialize};
-use crate::CompressionLevel;
+use crate::{CompressionLevel, CompressionType};
+use crate::codec::CodecRegistry;
+use crate::error::FzResult;
 
 /// 压缩统计信息
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -33,58 +35,72 @@ impl CompressionStats {
     }
 }
 
-/// Compress data using the specified compression algorithm
-pub fn compress_data(data: &[u8], compression_level: CompressionLevel) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    match compression_level {
-        CompressionLevel::None => Ok(data.to_vec()),
-        CompressionLevel::LZ4Fast => {
-            let compressed = lz4::block::compress(data, None, false)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(compressed)
-        }
-        CompressionLevel::LZ4High => {
-            let compressed = lz4::block::compress(data, None, true)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(compressed)
-        }
-        CompressionLevel::ZstdFast => {
-            let compressed = zstd::encode_all(&data[..], 1)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(compressed)
-        }
-        CompressionLevel::ZstdMedium => {
-            let compressed = zstd::encode_all(&data[..], 2)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(compressed)
-        }
-        CompressionLevel::ZstdHigh => {
-            let compressed = zstd::encode_all(&data[..], 3)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(compressed)
+/// 压缩参数表
+///
+/// 所有压缩路径（`compress_data`、`EventMessage::new` 等）都通过此表把
+/// `CompressionLevel` 映射为具体算法参数：
+///
+/// | CompressionLevel | 编解码器 | 参数 |
+/// |------------------|----------|------|
+/// | `None`           | none     | 不压缩 |
+/// | `LZ4Fast`        | lz4      | 快速模式 |
+/// | `LZ4High`        | lz4      | 高压缩模式，级别 9 |
+/// | `ZstdFast`       | zstd     | 级别 1 |
+/// | `ZstdMedium`     | zstd     | 级别 6 |
+/// | `ZstdHigh`       | zstd     | 级别 15 |
+/// | `ZstdMax`        | zstd     | 级别 22 |
+impl CompressionLevel {
+    /// 对应编解码器在 `CodecRegistry` 中的编号
+    pub fn codec_id(&self) -> u8 {
+        CompressionType::from(*self).wire_id()
+    }
+
+    /// 对应算法使用的压缩级别（LZ4 快速模式为 0）
+    pub fn codec_level(&self) -> i32 {
+        match self {
+            CompressionLevel::None | CompressionLevel::LZ4Fast => 0,
+            CompressionLevel::LZ4High => 9,
+            CompressionLevel::ZstdFast => 1,
+            CompressionLevel::ZstdMedium => 6,
+            CompressionLevel::ZstdHigh => 15,
+            CompressionLevel::ZstdMax => 22,
         }
-        CompressionLevel::ZstdMax => {
-            let compressed = zstd::encode_all(&data[..], 4)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(compressed)
+    }
+
+    /// 比当前级别更快的下一档（`LZ4Fast` 之后为不压缩）
+    pub fn faster(&self) -> Option<CompressionLevel> {
+        match self {
+            CompressionLevel::None => None,
+            CompressionLevel::LZ4Fast => Some(CompressionLevel::None),
+            CompressionLevel::LZ4High => Some(CompressionLevel::LZ4Fast),
+            CompressionLevel::ZstdFast => Some(CompressionLevel::LZ4Fast),
+            CompressionLevel::ZstdMedium => Some(CompressionLevel::ZstdFast),
+            CompressionLevel::ZstdHigh => Some(CompressionLevel::ZstdMedium),
+            CompressionLevel::ZstdMax => Some(CompressionLevel::ZstdHigh),
         }
     }
 }
 
+/// 默认的解压后最大字节数（64 MiB），防止解压炸弹
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+/// Compress data using the specified compression algorithm
+///
+/// LZ4 输出在开头携带 4 字节小端原始大小，解压时无需额外信息。
+pub fn compress_data(data: &[u8], compression_level: CompressionLevel) -> FzResult<Vec<u8>> {
+    CodecRegistry::global().compress(compression_level.codec_id(), data, compression_level.codec_level())
+}
+
 /// Decompress data using the specified compression algorithm
-pub fn decompress_data(data: &[u8], compression_level: CompressionLevel) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    match compression_level {
-        CompressionLevel::None => Ok(data.to_vec()),
-        CompressionLevel::LZ4High | CompressionLevel::LZ4Fast=> {
-            let decompressed = lz4::block::decompress(data, Some((data.len() * 4) as i32))
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(decompressed)
-        }
-        CompressionLevel::ZstdFast | CompressionLevel::ZstdMedium | CompressionLevel::ZstdHigh | CompressionLevel::ZstdMax => {
-            let decompressed = zstd::decode_all(&data[..])
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-            Ok(decompressed)
-        }
-    }
+///
+/// 解压后大小上限为 `DEFAULT_MAX_DECOMPRESSED_SIZE`。
+pub fn decompress_data(data: &[u8], compression_level: CompressionLevel) -> FzResult<Vec<u8>> {
+    decompress_data_with_limit(data, compression_level, DEFAULT_MAX_DECOMPRESSED_SIZE)
+}
+
+/// 解压数据，解压后超过 `max_size` 字节时返回错误
+pub fn decompress_data_with_limit(data: &[u8], compression_level: CompressionLevel, max_size: usize) -> FzResult<Vec<u8>> {
+    CodecRegistry::global().decompress(compression_level.codec_id(), data, max_size)
 }
 
 /// 压缩算法信息
@@ -104,6 +120,13 @@ pub struct CompressionConfig {
     pub level: u32,
     pub threshold_size: usize,
     pub max_compression_time_ms: u64,
+    /// 解压后允许的最大字节数
+    #[serde(default = "default_max_decompressed_size")]
+    pub max_decompressed_size: usize,
+}
+
+fn default_max_decompressed_size() -> usize {
+    DEFAULT_MAX_DECOMPRESSED_SIZE
 }
 
 impl Default for CompressionConfig {
@@ -114,6 +137,36 @@ impl Default for CompressionConfig {
             level: 1,
             threshold_size: 1024,
             max_compression_time_ms: 100,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
         }
     }
 }
+
+impl CompressionConfig {
+    /// 将 `algorithm`/`level` 映射为 `CompressionLevel`
+    ///
+    /// lz4：级别 ≤1 为 `LZ4Fast`，否则 `LZ4High`；
+    /// zstd：1-3 为 `ZstdFast`，4-9 为 `ZstdMedium`，10-18 为 `ZstdHigh`，19 及以上为 `ZstdMax`；
+    /// 未启用或未知算法为 `None`。
+    pub fn compression_level(&self) -> CompressionLevel {
+        if !self.enabled {
+            return CompressionLevel::None;
+        }
+        match self.algorithm.to_ascii_lowercase().as_str() {
+            "lz4" if self.level <= 1 => CompressionLevel::LZ4Fast,
+            "lz4" => CompressionLevel::LZ4High,
+            "zstd" => match self.level {
+                0..=3 => CompressionLevel::ZstdFast,
+                4..=9 => CompressionLevel::ZstdMedium,
+                10..=18 => CompressionLevel::ZstdHigh,
+                _ => CompressionLevel::ZstdMax,
+            },
+            _ => CompressionLevel::None,
+        }
+    }
+
+    /// 解压数据，解压后超过 `max_decompressed_size` 字节时返回错误
+    pub fn decompress(&self, data: &[u8], compression_level: CompressionLevel) -> FzResult<Vec<u8>> {
+        decompress_data_with_limit(data, compression_level, self.max_decompressed_size)
+    }
+}
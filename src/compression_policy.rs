@@ -0,0 +1,186 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::compression::CompressionConfig;
+use crate::events::{EventMessage, EventType};
+use crate::{CompressionLevel, SerializationProtocol};
+
+/// 判断事件类型是否值得压缩前需要的最少样本数
+pub const POLICY_MIN_SAMPLES: u64 = 32;
+/// 压缩后/原始大小的比率不低于此值时视为压缩无收益
+pub const POLICY_NO_GAIN_RATIO: f64 = 0.95;
+/// 停止压缩的事件类型每隔多少个事件重新尝试一次压缩
+pub const POLICY_PROBE_INTERVAL: u64 = 1024;
+
+/// 单个事件类型的压缩统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTypeCompressionStats {
+    /// 当前使用的压缩级别（超时后会降级）
+    pub level: CompressionLevel,
+    pub samples: u64,
+    pub original_bytes: u64,
+    pub compressed_bytes: u64,
+    /// 是否仍对该类型进行压缩
+    pub compression_enabled: bool,
+    /// 停止压缩后跳过的事件数
+    pub skipped: u64,
+}
+
+impl EventTypeCompressionStats {
+    fn new(level: CompressionLevel) -> Self {
+        Self {
+            level,
+            samples: 0,
+            original_bytes: 0,
+            compressed_bytes: 0,
+            compression_enabled: true,
+            skipped: 0,
+        }
+    }
+
+    /// 压缩后/原始大小的比率
+    pub fn ratio(&self) -> f64 {
+        if self.original_bytes > 0 {
+            self.compressed_bytes as f64 / self.original_bytes as f64
+        } else {
+            1.0
+        }
+    }
+}
+
+/// 自适应压缩策略
+///
+/// 根据 `CompressionConfig` 决定每个事件的压缩级别：
+/// - 小于 `threshold_size` 的负载不压缩；
+/// - 单次压缩超过 `max_compression_time_ms` 时，该事件类型降级到更快的级别；
+/// - 按事件类型统计压缩比，累计 `POLICY_MIN_SAMPLES` 个样本后仍无收益的类型停止压缩，
+///   之后每 `POLICY_PROBE_INTERVAL` 个事件重新尝试一次。
+#[derive(Debug)]
+pub struct CompressionPolicy {
+    config: CompressionConfig,
+    base_level: CompressionLevel,
+    stats: Mutex<HashMap<String, EventTypeCompressionStats>>,
+}
+
+impl CompressionPolicy {
+    pub fn new(config: CompressionConfig) -> Self {
+        let base_level = config.compression_level();
+        Self {
+            config,
+            base_level,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &CompressionConfig {
+        &self.config
+    }
+
+    /// 配置指定的压缩级别
+    pub fn base_level(&self) -> CompressionLevel {
+        self.base_level
+    }
+
+    /// 为事件选择压缩级别，返回 `None` 表示不压缩
+    pub fn select_level(&self, event_type: &EventType, payload_len: usize) -> CompressionLevel {
+        if self.base_level == CompressionLevel::None || payload_len < self.config.threshold_size {
+            return CompressionLevel::None;
+        }
+
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats
+            .entry(event_type.to_string())
+            .or_insert_with(|| EventTypeCompressionStats::new(self.base_level));
+
+        if entry.compression_enabled {
+            return entry.level;
+        }
+
+        entry.skipped += 1;
+        if entry.skipped.is_multiple_of(POLICY_PROBE_INTERVAL) {
+            entry.level
+        } else {
+            CompressionLevel::None
+        }
+    }
+
+    /// 记录一次压缩结果
+    pub fn record(
+        &self,
+        event_type: &EventType,
+        level: CompressionLevel,
+        original_len: usize,
+        compressed_len: usize,
+        elapsed: Duration,
+    ) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats
+            .entry(event_type.to_string())
+            .or_insert_with(|| EventTypeCompressionStats::new(self.base_level));
+
+        let sample_ratio = if original_len > 0 {
+            compressed_len as f64 / original_len as f64
+        } else {
+            1.0
+        };
+
+        if !entry.compression_enabled {
+            // 重新尝试：本次有收益则恢复压缩并重新统计
+            if sample_ratio < POLICY_NO_GAIN_RATIO {
+                *entry = EventTypeCompressionStats::new(entry.level);
+            } else {
+                return;
+            }
+        }
+
+        entry.samples += 1;
+        entry.original_bytes += original_len as u64;
+        entry.compressed_bytes += compressed_len as u64;
+
+        if elapsed > Duration::from_millis(self.config.max_compression_time_ms) && entry.level == level {
+            if let Some(faster) = level.faster() {
+                entry.level = faster;
+            }
+        }
+
+        if entry.level == CompressionLevel::None
+            || (entry.samples >= POLICY_MIN_SAMPLES && entry.ratio() >= POLICY_NO_GAIN_RATIO)
+        {
+            entry.compression_enabled = false;
+            entry.skipped = 0;
+        }
+    }
+
+    /// 获取各事件类型的压缩统计
+    pub fn stats(&self) -> HashMap<String, EventTypeCompressionStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 清空统计，所有事件类型恢复为配置的压缩级别
+    pub fn reset(&self) {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl EventMessage {
+    /// 按压缩策略创建事件消息，并将压缩结果反馈给策略
+    pub fn new_with_policy(
+        event_type: EventType,
+        data: Vec<u8>,
+        serialization_format: SerializationProtocol,
+        policy: &CompressionPolicy,
+    ) -> Self {
+        let level = policy.select_level(&event_type, data.len());
+        if level == CompressionLevel::None {
+            return Self::new(event_type, data, serialization_format, CompressionLevel::None, false);
+        }
+
+        let original_len = data.len();
+        let started = Instant::now();
+        let message = Self::new(event_type, data, serialization_format, level, true);
+        policy.record(&message.event_type, level, original_len, message.data.len(), started.elapsed());
+        message
+    }
+}
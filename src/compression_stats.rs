@@ -1,7 +1,6 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// 压缩统计配置
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -75,6 +74,7 @@ impl CompressionStatsCollector {
     }
 
     /// 记录压缩统计数据
+    #[allow(clippy::too_many_arguments)]
     pub fn record_compression_stats(
         &self,
         event_type: String,
@@ -90,10 +90,7 @@ impl CompressionStatsCollector {
             return;
         }
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let timestamp = crate::unix_now().as_secs();
 
         let total_saving = struct_size as i32 - final_compressed_size as i32;
         let total_ratio = if struct_size > 0 {
@@ -253,10 +250,7 @@ impl CompressionStatsCollector {
             return;
         }
 
-        let cutoff_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() - self.config.retention_seconds;
+        let cutoff_time = crate::unix_now().as_secs() - self.config.retention_seconds;
 
         if let Ok(mut event_stats) = self.event_stats.lock() {
             event_stats.retain(|s| s.timestamp >= cutoff_time);
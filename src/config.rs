@@ -1,24 +1,52 @@
 use serde::{Serialize, Deserialize};
 use std::time::Duration;
 
+use crate::codec::{CODEC_ID_LZ4, CODEC_ID_NONE, CODEC_ID_ZSTD};
+use crate::keyring::SigningKeyConfig;
+
 /// 序列化协议
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum SerializationProtocol {
     JSON,
     Bincode,
+    #[default]
     Auto,
 }
 
-impl Default for SerializationProtocol {
-    fn default() -> Self {
-        SerializationProtocol::Auto
+impl SerializationProtocol {
+    /// 帧头中使用的序列化编号
+    pub fn wire_id(&self) -> u8 {
+        match self {
+            SerializationProtocol::Auto => 0,
+            SerializationProtocol::JSON => 1,
+            SerializationProtocol::Bincode => 2,
+        }
+    }
+
+    /// 从帧头编号解析序列化协议
+    pub fn from_wire_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(SerializationProtocol::Auto),
+            1 => Some(SerializationProtocol::JSON),
+            2 => Some(SerializationProtocol::Bincode),
+            _ => None,
+        }
+    }
+
+    /// 解析出实际使用的协议（Auto 默认使用 Bincode）
+    pub fn resolve(&self) -> SerializationProtocol {
+        match self {
+            SerializationProtocol::Auto => SerializationProtocol::Bincode,
+            other => *other,
+        }
     }
 }
 
 /// 压缩级别
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CompressionLevel {
     None,
+    #[default]
     LZ4Fast,
     LZ4High,
     ZstdFast,
@@ -27,12 +55,6 @@ pub enum CompressionLevel {
     ZstdMax,
 }
 
-impl Default for CompressionLevel {
-    fn default() -> Self {
-        CompressionLevel::LZ4Fast
-    }
-}
-
 /// 压缩类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CompressionType {
@@ -52,6 +74,27 @@ impl From<CompressionLevel> for CompressionType {
     }
 }
 
+impl CompressionType {
+    /// 帧头中使用的编解码器编号
+    pub fn wire_id(&self) -> u8 {
+        match self {
+            CompressionType::None => CODEC_ID_NONE,
+            CompressionType::LZ4 => CODEC_ID_LZ4,
+            CompressionType::Zstd => CODEC_ID_ZSTD,
+        }
+    }
+
+    /// 从帧头编号解析压缩类型
+    pub fn from_wire_id(id: u8) -> Option<Self> {
+        match id {
+            CODEC_ID_NONE => Some(CompressionType::None),
+            CODEC_ID_LZ4 => Some(CompressionType::LZ4),
+            CODEC_ID_ZSTD => Some(CompressionType::Zstd),
+            _ => None,
+        }
+    }
+}
+
 /// 性能配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceProfile {
@@ -93,9 +136,12 @@ pub struct StreamClientConfig {
     pub protocol: SerializationProtocol,
     pub compression: CompressionLevel,
     pub auto_reconnect: bool,
+    #[serde(with = "crate::config_loader::human_duration")]
     pub reconnect_interval: Duration,
     pub max_reconnect_attempts: u32,
+    #[serde(with = "crate::config_loader::human_duration")]
     pub connection_timeout: Duration,
+    #[serde(with = "crate::config_loader::human_duration")]
     pub keep_alive_interval: Duration,
 }
 
@@ -135,6 +181,16 @@ pub struct ServerConfig {
     pub enable_stats_reporter: bool,
     pub heartbeat_interval_secs: u64,
     pub idle_timeout_secs: u64,
+    /// 会话令牌有效期（秒）
+    #[serde(default = "default_session_ttl_secs")]
+    pub session_ttl_secs: u64,
+    /// JWT 签名密钥环，用于不中断客户端的密钥轮换
+    #[serde(default)]
+    pub signing_keys: Vec<SigningKeyConfig>,
+}
+
+fn default_session_ttl_secs() -> u64 {
+    crate::session::DEFAULT_SESSION_TTL.as_secs()
 }
 
 impl Default for ServerConfig {
@@ -146,6 +202,8 @@ impl Default for ServerConfig {
             enable_stats_reporter: true,
             heartbeat_interval_secs: 30,
             idle_timeout_secs: 300,
+            session_ttl_secs: default_session_ttl_secs(),
+            signing_keys: Vec::new(),
         }
     }
 }
@@ -155,6 +213,12 @@ pub struct ConfigBuilder {
     config: StreamClientConfig,
 }
 
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ConfigBuilder {
     pub fn new() -> Self {
         Self {
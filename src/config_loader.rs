@@ -0,0 +1,249 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::config::{ClientConfig, ServerConfig, StreamClientConfig};
+use crate::error::{FzResult, FzStreamError};
+
+/// 指定配置文件路径的环境变量
+pub const CONFIG_PATH_ENV: &str = "FZSTREAM_CONFIG";
+
+/// 配置文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// 按扩展名识别格式（`.toml`、`.json`、`.yaml`/`.yml`）
+    pub fn from_path(path: &Path) -> FzResult<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            Some("yaml") | Some("yml") => Ok(ConfigFormat::Yaml),
+            _ => Err(FzStreamError::Config(format!(
+                "unsupported config file extension: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// 解析为 JSON 值
+    fn parse(&self, content: &str) -> Result<Value, String> {
+        match self {
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// 从文件加载配置，格式由扩展名决定，文件必须包含所有字段
+///
+/// 不合并默认值，也不应用环境变量，适用于读取完整导出的配置或没有 `Default` 的类型；
+/// 只写部分字段的配置文件请使用 `load_config`。
+pub fn load_from_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> FzResult<T> {
+    let path = path.as_ref();
+    from_value(read_file(path)?, path)
+}
+
+/// 可被环境变量覆盖的配置
+///
+/// 变量名为 `ENV_PREFIX` 加大写字段名，嵌套字段用 `__` 分隔，
+/// 例如 `FZSTREAM_SERVER_BIND_ADDRESS`、`FZSTREAM_PROFILE_CUSTOM_SETTINGS__ENABLE_METRICS`。
+/// 字符串字段原样使用，其他字段按 JSON 解析（`true`、`30`、`["a","b"]`），时长可写作 `"5s"`。
+pub trait EnvOverrides: Serialize + DeserializeOwned {
+    const ENV_PREFIX: &'static str;
+
+    /// 应用进程环境变量中的覆盖
+    fn apply_env_overrides(self) -> FzResult<Self> {
+        self.apply_overrides(std::env::vars())
+    }
+
+    /// 应用给定变量中以 `ENV_PREFIX` 开头的覆盖
+    fn apply_overrides<I>(self, vars: I) -> FzResult<Self>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut overrides: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(Self::ENV_PREFIX) && name.len() > Self::ENV_PREFIX.len())
+            .collect();
+        if overrides.is_empty() {
+            return Ok(self);
+        }
+        // 按名称排序，保证结果与环境变量顺序无关
+        overrides.sort();
+
+        let mut value = serde_json::to_value(&self)?;
+        for (name, raw) in &overrides {
+            let field_path: Vec<String> = name[Self::ENV_PREFIX.len()..]
+                .split("__")
+                .map(str::to_ascii_lowercase)
+                .collect();
+            apply_override::<Self>(&mut value, name, &field_path, raw)?;
+        }
+        serde_json::from_value(value)
+            .map_err(|e| FzStreamError::Config(format!("invalid environment override: {}", e)))
+    }
+}
+
+impl EnvOverrides for ServerConfig {
+    const ENV_PREFIX: &'static str = "FZSTREAM_SERVER_";
+}
+
+impl EnvOverrides for StreamClientConfig {
+    const ENV_PREFIX: &'static str = "FZSTREAM_CLIENT_";
+}
+
+impl EnvOverrides for ClientConfig {
+    const ENV_PREFIX: &'static str = "FZSTREAM_PROFILE_";
+}
+
+/// 加载配置，优先级从低到高：
+///
+/// 1. `T::default()`
+/// 2. 配置文件（`path`，未指定时使用 `FZSTREAM_CONFIG`；只需包含要修改的字段）
+/// 3. `FZSTREAM_*` 环境变量
+pub fn load_config<T: EnvOverrides + Default>(path: Option<&Path>) -> FzResult<T> {
+    let env_path = std::env::var_os(CONFIG_PATH_ENV);
+    let path = path.or(env_path.as_deref().map(Path::new));
+    let config = match path {
+        Some(path) => {
+            let mut value = serde_json::to_value(T::default())?;
+            merge(&mut value, read_file(path)?);
+            from_value(value, path)?
+        }
+        None => T::default(),
+    };
+    config.apply_env_overrides()
+}
+
+fn read_file(path: &Path) -> FzResult<Value> {
+    let format = ConfigFormat::from_path(path)?;
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| FzStreamError::Config(format!("failed to read config {}: {}", path.display(), e)))?;
+    format
+        .parse(&content)
+        .map_err(|e| FzStreamError::Config(format!("failed to parse config {}: {}", path.display(), e)))
+}
+
+fn from_value<T: DeserializeOwned>(value: Value, path: &Path) -> FzResult<T> {
+    serde_json::from_value(value)
+        .map_err(|e| FzStreamError::Config(format!("invalid config {}: {}", path.display(), e)))
+}
+
+/// 将 `overlay` 递归合并到 `base`，对象按字段合并，其他值直接替换
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn apply_override<T: DeserializeOwned>(root: &mut Value, name: &str, field_path: &[String], raw: &str) -> FzResult<()> {
+    if field_path.iter().any(|key| key.is_empty()) {
+        return Err(FzStreamError::Config(format!("invalid environment override name: {}", name)));
+    }
+    // 顶层字段必须存在，避免拼写错误被静默忽略
+    if !root.as_object().is_some_and(|object| object.contains_key(&field_path[0])) {
+        return Err(FzStreamError::Config(format!("unknown config field in {}", name)));
+    }
+
+    let existing = field_path.iter().try_fold(&*root, |value, key| value.get(key));
+    let value = match existing {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        Some(Value::Null) | None => {
+            // 类型未知（Option 字段或新的映射项）时先按 JSON 解析，整体无法反序列化时退回字符串
+            match serde_json::from_str::<Value>(raw) {
+                Ok(parsed) => {
+                    let mut candidate = root.clone();
+                    insert_at(&mut candidate, name, field_path, parsed.clone())?;
+                    if serde_json::from_value::<T>(candidate).is_ok() {
+                        parsed
+                    } else {
+                        Value::String(raw.to_string())
+                    }
+                }
+                Err(_) => Value::String(raw.to_string()),
+            }
+        }
+        Some(_) => serde_json::from_str(raw)
+            .map_err(|e| FzStreamError::Config(format!("invalid value for {}: {}", name, e)))?,
+    };
+    insert_at(root, name, field_path, value)
+}
+
+/// 按路径写入值，中间缺少的对象会被创建
+fn insert_at(root: &mut Value, name: &str, field_path: &[String], value: Value) -> FzResult<()> {
+    let mut current = root;
+    for key in &field_path[..field_path.len() - 1] {
+        current = match current {
+            Value::Object(object) => object.entry(key.clone()).or_insert_with(|| Value::Object(Map::new())),
+            _ => return Err(FzStreamError::Config(format!("{} does not refer to a nested field", name))),
+        };
+    }
+    match current {
+        Value::Object(object) => {
+            object.insert(field_path[field_path.len() - 1].clone(), value);
+            Ok(())
+        }
+        _ => Err(FzStreamError::Config(format!("{} does not refer to a nested field", name))),
+    }
+}
+
+/// `Duration` 字段的可读格式（`"5s"`、`"1m 30s"`、`"250ms"`）
+///
+/// 反序列化时也接受整数秒和 serde 默认的 `{secs, nanos}` 格式。
+/// 可读格式依赖 `deserialize_any`，Bincode 等非自描述格式（`is_human_readable` 为 false）
+/// 直接使用 `Duration` 自身的编码。
+pub mod human_duration {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DurationRepr {
+        Text(String),
+        Secs(u64),
+        Struct { secs: u64, #[serde(default)] nanos: u32 },
+    }
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return duration.serialize(serializer);
+        }
+        serializer.serialize_str(&humantime::format_duration(*duration).to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Duration::deserialize(deserializer);
+        }
+        match DurationRepr::deserialize(deserializer)? {
+            DurationRepr::Text(text) => match text.trim().parse::<u64>() {
+                Ok(secs) => Ok(Duration::from_secs(secs)),
+                Err(_) => humantime::parse_duration(text.trim())
+                    .map_err(|e| serde::de::Error::custom(format!("invalid duration {:?}: {}", text, e))),
+            },
+            DurationRepr::Secs(secs) => Ok(Duration::from_secs(secs)),
+            DurationRepr::Struct { secs, nanos } => Ok(Duration::new(secs, nanos)),
+        }
+    }
+}
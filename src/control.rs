@@ -0,0 +1,216 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::batch::EventBatch;
+use crate::codec::CODEC_ID_NONE;
+use crate::error::FzStreamError;
+use crate::events::{EventMessage, EventType, EventTypeFilter};
+use crate::replay::{ResumeRequest, ResumeResponse};
+use crate::wire::{FrameKind, WireMessage};
+use crate::SerializationProtocol;
+
+/// 关闭连接的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CloseReason {
+    /// 正常关闭
+    Normal,
+    /// 空闲超时
+    IdleTimeout,
+    /// 认证失败或令牌失效
+    Unauthorized,
+    /// 对端违反协议
+    ProtocolError,
+    /// 客户端消费过慢
+    SlowConsumer,
+    /// 服务器关闭
+    ServerShutdown,
+}
+
+impl fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloseReason::Normal => write!(f, "normal"),
+            CloseReason::IdleTimeout => write!(f, "idle timeout"),
+            CloseReason::Unauthorized => write!(f, "unauthorized"),
+            CloseReason::ProtocolError => write!(f, "protocol error"),
+            CloseReason::SlowConsumer => write!(f, "slow consumer"),
+            CloseReason::ServerShutdown => write!(f, "server shutdown"),
+        }
+    }
+}
+
+/// 控制消息
+///
+/// 认证完成后在同一连接上发送，用于调整订阅、保活和确认，无需重新连接。
+/// `Ping`/`Pong` 和 `Close` 双向可用，其余由客户端发送。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControlMessage {
+    /// 订阅事件类型（追加到当前过滤器）
+    Subscribe { event_types: Vec<EventType> },
+    /// 取消订阅事件类型
+    Unsubscribe { event_types: Vec<EventType> },
+    /// 替换整个过滤器
+    UpdateFilter { filter: EventTypeFilter },
+    Ping { nonce: u64, timestamp: u64 },
+    Pong { nonce: u64, timestamp: u64 },
+    /// 确认已处理到 `sequence`（含）的事件
+    Ack { sequence: u64 },
+    /// 请求从断点续传
+    Resume(ResumeRequest),
+    Close { reason: CloseReason, message: Option<String> },
+}
+
+impl ControlMessage {
+    /// 以当前时间创建 Ping
+    pub fn ping(nonce: u64) -> Self {
+        ControlMessage::Ping { nonce, timestamp: crate::unix_now().as_millis() as u64 }
+    }
+
+    /// 创建对应的 Pong，非 Ping 消息返回 None
+    pub fn pong_for(&self) -> Option<Self> {
+        match self {
+            ControlMessage::Ping { nonce, .. } => Some(ControlMessage::Pong {
+                nonce: *nonce,
+                timestamp: crate::unix_now().as_millis() as u64,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn close(reason: CloseReason, message: impl Into<String>) -> Self {
+        ControlMessage::Close { reason, message: Some(message.into()) }
+    }
+
+    /// 是否会修改订阅过滤器
+    pub fn is_filter_change(&self) -> bool {
+        matches!(
+            self,
+            ControlMessage::Subscribe { .. } | ControlMessage::Unsubscribe { .. } | ControlMessage::UpdateFilter { .. }
+        )
+    }
+
+    /// 将订阅变更应用到过滤器，返回过滤器是否被修改
+    pub fn apply_to_filter(&self, filter: &mut EventTypeFilter) -> bool {
+        match self {
+            ControlMessage::Subscribe { event_types } => {
+                for event_type in event_types {
+                    filter.remove_blocked_type(event_type);
+                    if !filter.allow_all {
+                        filter.add_allowed_type(event_type.clone());
+                    }
+                }
+                true
+            }
+            ControlMessage::Unsubscribe { event_types } => {
+                for event_type in event_types {
+                    if filter.allow_all {
+                        filter.add_blocked_type(event_type.clone());
+                    } else {
+                        filter.remove_allowed_type(event_type);
+                    }
+                }
+                true
+            }
+            ControlMessage::UpdateFilter { filter: new_filter } => {
+                *filter = new_filter.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl WireMessage for ControlMessage {
+    const KIND: FrameKind = FrameKind::Control;
+
+    fn serialization_format(&self) -> SerializationProtocol {
+        SerializationProtocol::Bincode
+    }
+
+    fn codec_id(&self) -> u8 {
+        CODEC_ID_NONE
+    }
+
+    fn data_flags(&self) -> u8 {
+        0
+    }
+}
+
+/// 服务器发往客户端的消息
+///
+/// 在同一条流上复用事件数据和控制应答。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Event(EventMessage),
+    Batch(EventBatch),
+    /// 服务器主动发送的控制消息（Ping、Pong、Close）
+    Control(ControlMessage),
+    /// 订阅变更后的生效过滤器
+    FilterUpdated { filter: EventTypeFilter },
+    /// 续传请求的应答
+    Resumed(ResumeResponse),
+    /// 控制消息处理失败，`code` 为错误的稳定数值码
+    Error { code: u32, message: String },
+}
+
+impl ServerMessage {
+    /// 根据错误构造错误应答
+    pub fn error(error: &FzStreamError) -> Self {
+        ServerMessage::Error {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<EventMessage> for ServerMessage {
+    fn from(message: EventMessage) -> Self {
+        ServerMessage::Event(message)
+    }
+}
+
+impl From<EventBatch> for ServerMessage {
+    fn from(batch: EventBatch) -> Self {
+        ServerMessage::Batch(batch)
+    }
+}
+
+impl From<ControlMessage> for ServerMessage {
+    fn from(message: ControlMessage) -> Self {
+        ServerMessage::Control(message)
+    }
+}
+
+impl WireMessage for ServerMessage {
+    const KIND: FrameKind = FrameKind::Server;
+
+    fn serialization_format(&self) -> SerializationProtocol {
+        match self {
+            ServerMessage::Event(message) => message.serialization_format(),
+            ServerMessage::Batch(batch) => batch.serialization_format(),
+            _ => SerializationProtocol::Bincode,
+        }
+    }
+
+    fn codec_id(&self) -> u8 {
+        match self {
+            ServerMessage::Event(message) => message.codec_id(),
+            ServerMessage::Batch(batch) => batch.codec_id(),
+            _ => CODEC_ID_NONE,
+        }
+    }
+
+    fn set_codec_id(&mut self, codec_id: u8) {
+        if let ServerMessage::Event(message) = self {
+            message.set_codec_id(codec_id);
+        }
+    }
+
+    fn data_flags(&self) -> u8 {
+        match self {
+            ServerMessage::Event(message) => message.data_flags(),
+            ServerMessage::Batch(batch) => batch.data_flags(),
+            _ => 0,
+        }
+    }
+}
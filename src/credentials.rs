@@ -0,0 +1,216 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use ring::digest;
+
+use crate::error::{FzResult, FzStreamError};
+
+/// 计算令牌的 SHA-256 哈希（小写十六进制）
+///
+/// 凭据存储只保存哈希，不保存令牌明文。
+pub fn hash_token(token: &str) -> String {
+    to_hex(digest::digest(&digest::SHA256, token.as_bytes()).as_ref())
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 与内容无关的耗时比较，避免通过响应时间猜测密钥
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// 凭据记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialRecord {
+    /// 凭据名称（用于日志和审计，不参与验证）
+    pub name: String,
+    /// 令牌的 SHA-256 哈希
+    pub token_hash: String,
+    pub permissions: Vec<String>,
+    /// 过期时间（Unix 秒），None 表示永不过期
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl CredentialRecord {
+    /// 根据令牌明文创建记录
+    pub fn new(name: impl Into<String>, token: &str, permissions: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            token_hash: hash_token(token),
+            permissions,
+            expires_at: None,
+        }
+    }
+
+    pub fn with_expires_at(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// 凭据存储
+pub trait CredentialStore: Send + Sync {
+    /// 按令牌哈希查找凭据
+    fn lookup(&self, token_hash: &str) -> FzResult<Option<CredentialRecord>>;
+}
+
+/// 内存凭据存储
+#[derive(Debug, Default)]
+pub struct MemoryCredentialStore {
+    records: RwLock<HashMap<String, CredentialRecord>>,
+}
+
+impl MemoryCredentialStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开发用演示令牌（仅在启用 `dev-tokens` 特性时可用）
+    #[cfg(feature = "dev-tokens")]
+    pub fn with_dev_tokens() -> Self {
+        let store = Self::new();
+        let permissions = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        store.insert(CredentialRecord::new("demo", "demo_token_12345", permissions(&["read", "stream"])));
+        store.insert(CredentialRecord::new("test", "test_auth_token", permissions(&["read", "stream", "write"])));
+        store.insert(CredentialRecord::new(
+            "admin",
+            "admin_super_token_999",
+            permissions(&["read", "stream", "write", "admin"]),
+        ));
+        store
+    }
+
+    /// 添加或替换凭据
+    pub fn insert(&self, record: CredentialRecord) {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        records.insert(record.token_hash.clone(), record);
+    }
+
+    /// 按令牌哈希删除凭据
+    pub fn remove(&self, token_hash: &str) -> Option<CredentialRecord> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        records.remove(token_hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前所有凭据
+    pub fn records(&self) -> Vec<CredentialRecord> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<CredentialRecord> = records.values().cloned().collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    fn replace_all(&self, list: Vec<CredentialRecord>) {
+        let map = list.into_iter().map(|record| (record.token_hash.clone(), record)).collect();
+        *self.records.write().unwrap_or_else(|e| e.into_inner()) = map;
+    }
+}
+
+impl CredentialStore for MemoryCredentialStore {
+    fn lookup(&self, token_hash: &str) -> FzResult<Option<CredentialRecord>> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        Ok(records.get(token_hash).cloned())
+    }
+}
+
+/// 文件凭据存储
+///
+/// 文件内容为 `CredentialRecord` 的 JSON 数组，只保存令牌哈希。
+#[derive(Debug)]
+pub struct FileCredentialStore {
+    path: PathBuf,
+    inner: MemoryCredentialStore,
+}
+
+impl FileCredentialStore {
+    /// 打开凭据文件，文件不存在时返回错误
+    pub fn open(path: impl AsRef<Path>) -> FzResult<Self> {
+        let store = Self {
+            path: path.as_ref().to_path_buf(),
+            inner: MemoryCredentialStore::new(),
+        };
+        store.reload()?;
+        Ok(store)
+    }
+
+    /// 创建空的凭据文件（已存在时覆盖）
+    pub fn create(path: impl AsRef<Path>) -> FzResult<Self> {
+        let store = Self {
+            path: path.as_ref().to_path_buf(),
+            inner: MemoryCredentialStore::new(),
+        };
+        store.save()?;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 重新读取凭据文件
+    pub fn reload(&self) -> FzResult<()> {
+        let content = std::fs::read(&self.path).map_err(|e| {
+            FzStreamError::Config(format!("failed to read credentials {}: {}", self.path.display(), e))
+        })?;
+        let list: Vec<CredentialRecord> = serde_json::from_slice(&content).map_err(|e| {
+            FzStreamError::Config(format!("failed to parse credentials {}: {}", self.path.display(), e))
+        })?;
+        self.inner.replace_all(list);
+        Ok(())
+    }
+
+    /// 添加或替换凭据并写回文件
+    pub fn insert(&self, record: CredentialRecord) -> FzResult<()> {
+        self.inner.insert(record);
+        self.save()
+    }
+
+    /// 删除凭据并写回文件
+    pub fn remove(&self, token_hash: &str) -> FzResult<Option<CredentialRecord>> {
+        let removed = self.inner.remove(token_hash);
+        if removed.is_some() {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    fn save(&self) -> FzResult<()> {
+        let content = serde_json::to_vec_pretty(&self.inner.records())?;
+        // 先写临时文件再重命名，避免写入中途崩溃留下损坏的文件
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, content)
+            .and_then(|_| std::fs::rename(&tmp_path, &self.path))
+            .map_err(|e| FzStreamError::Config(format!("failed to write credentials {}: {}", self.path.display(), e)))
+    }
+}
+
+impl CredentialStore for FileCredentialStore {
+    fn lookup(&self, token_hash: &str) -> FzResult<Option<CredentialRecord>> {
+        self.inner.lookup(token_hash)
+    }
+}
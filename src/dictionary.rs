@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+use crate::codec::check_decompressed_size;
+use crate::compression::CompressionInfo;
+use crate::error::{FzResult, FzStreamError};
+
+/// zstd 字典魔数（小端）
+const ZSTD_DICT_MAGIC: [u8; 4] = [0x37, 0xA4, 0x30, 0xEC];
+
+/// 字典文件扩展名
+pub const DICTIONARY_FILE_EXTENSION: &str = "dict";
+
+/// 默认训练出的字典大小（16 KiB）
+pub const DEFAULT_DICTIONARY_SIZE: usize = 16 * 1024;
+
+/// 从序列化后的事件样本训练 zstd 字典
+///
+/// 样本应为未压缩的事件数据（即 `EventMessage::new` 的 `data` 入参），
+/// 样本数量过少时 zstd 会训练失败。
+pub fn train_dictionary<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> FzResult<Vec<u8>> {
+    zstd::dict::from_samples(samples, max_size)
+        .map_err(|e| FzStreamError::Compression(format!("zstd dictionary training: {}", e)))
+}
+
+/// 读取 zstd 字典头中的字典编号（非 zstd 格式字典返回 None）
+pub fn dictionary_id(dictionary: &[u8]) -> Option<u32> {
+    if dictionary.len() < 8 || dictionary[..4] != ZSTD_DICT_MAGIC {
+        return None;
+    }
+    let id = u32::from_le_bytes([dictionary[4], dictionary[5], dictionary[6], dictionary[7]]);
+    if id == 0 {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+/// 已加载的 zstd 字典
+///
+/// 解压字典在加载时预处理，压缩字典按压缩级别懒加载并缓存。
+pub struct ZstdDictionary {
+    id: u32,
+    data: Vec<u8>,
+    decoder: DecoderDictionary<'static>,
+    encoders: Mutex<HashMap<i32, Arc<EncoderDictionary<'static>>>>,
+}
+
+impl std::fmt::Debug for ZstdDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZstdDictionary")
+            .field("id", &self.id)
+            .field("size", &self.data.len())
+            .finish()
+    }
+}
+
+impl ZstdDictionary {
+    /// 使用指定编号创建字典
+    pub fn new(id: u32, data: Vec<u8>) -> Self {
+        let decoder = DecoderDictionary::copy(&data);
+        Self {
+            id,
+            data,
+            decoder,
+            encoders: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 从 zstd 格式字典创建，编号取自字典头
+    pub fn from_bytes(data: Vec<u8>) -> FzResult<Self> {
+        let id = dictionary_id(&data)
+            .ok_or_else(|| FzStreamError::Config("dictionary has no zstd dictionary id".to_string()))?;
+        Ok(Self::new(id, data))
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 使用该字典时的压缩算法信息
+    pub fn compression_info(&self, level: i32) -> CompressionInfo {
+        CompressionInfo {
+            algorithm: "zstd".to_string(),
+            level: level.max(0) as u32,
+            dictionary_size: Some(self.data.len()),
+            block_size: None,
+        }
+    }
+
+    fn encoder(&self, level: i32) -> Arc<EncoderDictionary<'static>> {
+        let mut encoders = self.encoders.lock().unwrap_or_else(|e| e.into_inner());
+        encoders
+            .entry(level)
+            .or_insert_with(|| Arc::new(EncoderDictionary::copy(&self.data, level)))
+            .clone()
+    }
+
+    /// 使用字典压缩
+    pub fn compress(&self, data: &[u8], level: i32) -> FzResult<Vec<u8>> {
+        let encoder_dict = self.encoder(level);
+        let mut encoder = zstd::stream::write::Encoder::with_prepared_dictionary(Vec::new(), &encoder_dict)
+            .map_err(|e| FzStreamError::Compression(format!("zstd dictionary {}: {}", self.id, e)))?;
+        encoder
+            .write_all(data)
+            .map_err(|e| FzStreamError::Compression(format!("zstd dictionary {}: {}", self.id, e)))?;
+        encoder
+            .finish()
+            .map_err(|e| FzStreamError::Compression(format!("zstd dictionary {}: {}", self.id, e)))
+    }
+
+    /// 使用字典解压，解压后超过 `max_size` 字节时返回错误
+    pub fn decompress(&self, data: &[u8], max_size: usize) -> FzResult<Vec<u8>> {
+        let decoder = zstd::stream::read::Decoder::with_prepared_dictionary(data, &self.decoder)
+            .map_err(|e| FzStreamError::Decompression(format!("zstd dictionary {}: {}", self.id, e)))?;
+
+        let mut output = Vec::new();
+        // 多读一个字节以判断是否超过上限
+        decoder
+            .take(max_size as u64 + 1)
+            .read_to_end(&mut output)
+            .map_err(|e| FzStreamError::Decompression(format!("zstd dictionary {}: {}", self.id, e)))?;
+        check_decompressed_size(output.len(), max_size)?;
+        Ok(output)
+    }
+}
+
+/// 字典存储
+///
+/// 服务器和客户端加载同一组字典文件，消息中只携带字典编号。
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryStore {
+    dictionaries: HashMap<u32, Arc<ZstdDictionary>>,
+}
+
+impl DictionaryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从目录加载所有 `.dict` 文件
+    pub fn load_from_dir(dir: impl AsRef<Path>) -> FzResult<Self> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| FzStreamError::Config(format!("failed to read dictionary dir {}: {}", dir.display(), e)))?;
+
+        let mut store = Self::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| FzStreamError::Config(format!("failed to read dictionary dir {}: {}", dir.display(), e)))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some(DICTIONARY_FILE_EXTENSION) {
+                store.load_file(&path)?;
+            }
+        }
+        Ok(store)
+    }
+
+    /// 加载单个 zstd 字典文件，返回字典编号
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> FzResult<u32> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| FzStreamError::Config(format!("failed to read dictionary {}: {}", path.display(), e)))?;
+        self.insert_bytes(data)
+    }
+
+    /// 将字典保存为 `<dir>/<id>.dict`
+    pub fn save_to_dir(&self, dir: impl AsRef<Path>) -> FzResult<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .map_err(|e| FzStreamError::Config(format!("failed to create dictionary dir {}: {}", dir.display(), e)))?;
+
+        for dictionary in self.dictionaries.values() {
+            let path = dir.join(format!("{}.{}", dictionary.id(), DICTIONARY_FILE_EXTENSION));
+            std::fs::write(&path, dictionary.as_bytes())
+                .map_err(|e| FzStreamError::Config(format!("failed to write dictionary {}: {}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+
+    /// 添加 zstd 格式字典，返回字典编号
+    pub fn insert_bytes(&mut self, data: Vec<u8>) -> FzResult<u32> {
+        let dictionary = ZstdDictionary::from_bytes(data)?;
+        let id = dictionary.id();
+        self.insert(dictionary);
+        Ok(id)
+    }
+
+    /// 添加字典，替换同编号的旧字典
+    pub fn insert(&mut self, dictionary: ZstdDictionary) {
+        self.dictionaries.insert(dictionary.id(), Arc::new(dictionary));
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Arc<ZstdDictionary>> {
+        self.dictionaries.get(&id)
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.dictionaries.contains_key(&id)
+    }
+
+    /// 已加载的字典编号
+    pub fn ids(&self) -> Vec<u32> {
+        self.dictionaries.keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dictionaries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dictionaries.is_empty()
+    }
+
+    /// 使用指定字典压缩
+    pub fn compress(&self, id: u32, data: &[u8], level: i32) -> FzResult<Vec<u8>> {
+        self.get(id)
+            .ok_or_else(|| FzStreamError::Compression(format!("unknown dictionary id: {}", id)))?
+            .compress(data, level)
+    }
+
+    /// 使用指定字典解压
+    pub fn decompress(&self, id: u32, data: &[u8], max_size: usize) -> FzResult<Vec<u8>> {
+        self.get(id)
+            .ok_or_else(|| FzStreamError::Decompression(format!("unknown dictionary id: {}", id)))?
+            .decompress(data, max_size)
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::wire::FrameError;
+
+/// 统一结果类型
+pub type FzResult<T> = Result<T, FzStreamError>;
+
+/// 认证失败码
+///
+/// 数值稳定，可直接放入 `AuthResponse::Failure.code`。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AuthErrorCode {
+    /// 无法识别的令牌
+    InvalidToken,
+    /// 令牌格式错误
+    MalformedToken,
+    /// 令牌已过期
+    ExpiredToken,
+    /// 令牌尚未生效（`nbf` 或 `iat` 在未来）
+    TokenNotYetValid,
+    /// 认证消息时间戳超出允许的窗口
+    StaleTimestamp,
+    /// 认证消息的 nonce 已被使用（重放）
+    ReplayDetected,
+    /// 认证消息缺少 nonce
+    MissingNonce,
+    /// 令牌、用户或密钥已被吊销
+    TokenRevoked,
+    /// 窗口内缓存的认证 nonce 已满，暂时无法接受新的认证消息
+    ReplayCacheFull,
+    /// 令牌没有所需权限
+    PermissionDenied,
+    /// 超出事件或流量速率配额
+    RateLimited,
+    /// 超出连接数配额
+    ConnectionLimitExceeded,
+}
+
+impl AuthErrorCode {
+    /// 稳定的数值错误码
+    pub fn code(&self) -> u32 {
+        match self {
+            AuthErrorCode::InvalidToken => 4010,
+            AuthErrorCode::MalformedToken => 4011,
+            AuthErrorCode::ExpiredToken => 4012,
+            AuthErrorCode::TokenNotYetValid => 4013,
+            AuthErrorCode::StaleTimestamp => 4014,
+            AuthErrorCode::ReplayDetected => 4015,
+            AuthErrorCode::MissingNonce => 4016,
+            AuthErrorCode::TokenRevoked => 4017,
+            AuthErrorCode::ReplayCacheFull => 4018,
+            AuthErrorCode::PermissionDenied => 4030,
+            AuthErrorCode::RateLimited => 4290,
+            AuthErrorCode::ConnectionLimitExceeded => 4291,
+        }
+    }
+
+    /// 从数值错误码解析
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            4010 => Some(AuthErrorCode::InvalidToken),
+            4011 => Some(AuthErrorCode::MalformedToken),
+            4012 => Some(AuthErrorCode::ExpiredToken),
+            4013 => Some(AuthErrorCode::TokenNotYetValid),
+            4014 => Some(AuthErrorCode::StaleTimestamp),
+            4015 => Some(AuthErrorCode::ReplayDetected),
+            4016 => Some(AuthErrorCode::MissingNonce),
+            4017 => Some(AuthErrorCode::TokenRevoked),
+            4018 => Some(AuthErrorCode::ReplayCacheFull),
+            4030 => Some(AuthErrorCode::PermissionDenied),
+            4290 => Some(AuthErrorCode::RateLimited),
+            4291 => Some(AuthErrorCode::ConnectionLimitExceeded),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AuthErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AuthErrorCode::InvalidToken => "invalid token",
+            AuthErrorCode::MalformedToken => "malformed token",
+            AuthErrorCode::ExpiredToken => "token expired",
+            AuthErrorCode::TokenNotYetValid => "token not yet valid",
+            AuthErrorCode::StaleTimestamp => "stale auth timestamp",
+            AuthErrorCode::ReplayDetected => "replayed auth message",
+            AuthErrorCode::MissingNonce => "missing auth nonce",
+            AuthErrorCode::TokenRevoked => "token revoked",
+            AuthErrorCode::ReplayCacheFull => "auth nonce cache full",
+            AuthErrorCode::PermissionDenied => "permission denied",
+            AuthErrorCode::RateLimited => "rate limit exceeded",
+            AuthErrorCode::ConnectionLimitExceeded => "connection limit exceeded",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// FZ Stream 统一错误类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum FzStreamError {
+    /// 压缩失败
+    Compression(String),
+    /// 解压失败
+    Decompression(String),
+    /// 序列化/反序列化失败
+    Serialization(String),
+    /// 认证失败
+    Auth { code: AuthErrorCode, message: String },
+    /// 帧编解码失败
+    Frame(FrameError),
+    /// 配置错误
+    Config(String),
+    /// 握手协商失败（没有双方都支持的组合）
+    Negotiation(String),
+    /// 令牌签发失败
+    Signing(String),
+}
+
+impl FzStreamError {
+    pub fn auth(code: AuthErrorCode, message: impl Into<String>) -> Self {
+        FzStreamError::Auth { code, message: message.into() }
+    }
+
+    /// 稳定的数值错误码
+    ///
+    /// 1001-1006 为通用错误，11xx 为帧错误，4xxx 为认证错误。
+    pub fn code(&self) -> u32 {
+        match self {
+            FzStreamError::Compression(_) => 1001,
+            FzStreamError::Decompression(_) => 1002,
+            FzStreamError::Serialization(_) => 1003,
+            FzStreamError::Config(_) => 1004,
+            FzStreamError::Negotiation(_) => 1005,
+            FzStreamError::Signing(_) => 1006,
+            FzStreamError::Frame(e) => e.code(),
+            FzStreamError::Auth { code, .. } => code.code(),
+        }
+    }
+
+    /// 认证失败码（非认证错误返回 None）
+    pub fn auth_code(&self) -> Option<AuthErrorCode> {
+        match self {
+            FzStreamError::Auth { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FzStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FzStreamError::Compression(msg) => write!(f, "compression failed: {}", msg),
+            FzStreamError::Decompression(msg) => write!(f, "decompression failed: {}", msg),
+            FzStreamError::Serialization(msg) => write!(f, "serialization failed: {}", msg),
+            FzStreamError::Auth { code, message } => write!(f, "{}: {}", code, message),
+            FzStreamError::Frame(e) => write!(f, "{}", e),
+            FzStreamError::Config(msg) => write!(f, "invalid configuration: {}", msg),
+            FzStreamError::Negotiation(msg) => write!(f, "handshake negotiation failed: {}", msg),
+            FzStreamError::Signing(msg) => write!(f, "token signing failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FzStreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FzStreamError::Frame(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FrameError> for FzStreamError {
+    fn from(e: FrameError) -> Self {
+        FzStreamError::Frame(e)
+    }
+}
+
+impl From<bincode::Error> for FzStreamError {
+    fn from(e: bincode::Error) -> Self {
+        FzStreamError::Serialization(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for FzStreamError {
+    fn from(e: serde_json::Error) -> Self {
+        FzStreamError::Serialization(e.to_string())
+    }
+}
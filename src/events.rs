@@ -1,10 +1,83 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
-use crate::{CompressionLevel, SerializationProtocol};
+use crate::{CompressionLevel, CompressionType, SerializationProtocol};
+use crate::compression::{compress_data, decompress_data_with_limit, CompressionConfig, DEFAULT_MAX_DECOMPRESSED_SIZE};
+use crate::codec::{Codec, CodecRegistry};
+use crate::dictionary::DictionaryStore;
+use crate::error::{FzResult, FzStreamError};
 pub use solana_streamer_sdk::streaming::event_parser::common::EventType;
 pub use solana_streamer_sdk::streaming::event_parser::common::EventMetadata;
 
+/// 生成 `ALL_EVENT_TYPES`，并对 `EventType` 做穷尽匹配，上游新增变体时无法通过编译
+macro_rules! all_event_types {
+    ($($variant:ident,)*) => {
+        /// 所有标准事件类型（不含 `Unknown`）
+        pub const ALL_EVENT_TYPES: &[EventType] = &[$(EventType::$variant,)*];
+
+        const _: fn(&EventType) = |event_type| match event_type {
+            $(EventType::$variant)|* | EventType::Unknown => {}
+        };
+    };
+}
+
+all_event_types! {
+    PumpSwapBuy,
+    PumpSwapSell,
+    PumpSwapCreatePool,
+    PumpSwapDeposit,
+    PumpSwapWithdraw,
+    PumpFunCreateToken,
+    PumpFunBuy,
+    PumpFunSell,
+    PumpFunMigrate,
+    BonkBuyExactIn,
+    BonkBuyExactOut,
+    BonkSellExactIn,
+    BonkSellExactOut,
+    BonkInitialize,
+    BonkInitializeV2,
+    BonkInitializeWithToken2022,
+    BonkMigrateToAmm,
+    BonkMigrateToCpswap,
+    RaydiumCpmmSwapBaseInput,
+    RaydiumCpmmSwapBaseOutput,
+    RaydiumCpmmDeposit,
+    RaydiumCpmmInitialize,
+    RaydiumCpmmWithdraw,
+    RaydiumClmmSwap,
+    RaydiumClmmSwapV2,
+    RaydiumClmmClosePosition,
+    RaydiumClmmIncreaseLiquidityV2,
+    RaydiumClmmDecreaseLiquidityV2,
+    RaydiumClmmCreatePool,
+    RaydiumClmmOpenPositionWithToken22Nft,
+    RaydiumClmmOpenPositionV2,
+    RaydiumAmmV4SwapBaseIn,
+    RaydiumAmmV4SwapBaseOut,
+    RaydiumAmmV4Deposit,
+    RaydiumAmmV4Initialize2,
+    RaydiumAmmV4Withdraw,
+    RaydiumAmmV4WithdrawPnl,
+    AccountRaydiumAmmV4AmmInfo,
+    AccountPumpSwapGlobalConfig,
+    AccountPumpSwapPool,
+    AccountBonkPoolState,
+    AccountBonkGlobalConfig,
+    AccountBonkPlatformConfig,
+    AccountBonkVestingRecord,
+    AccountPumpFunBondingCurve,
+    AccountPumpFunGlobal,
+    AccountRaydiumClmmAmmConfig,
+    AccountRaydiumClmmPoolState,
+    AccountRaydiumClmmTickArrayState,
+    AccountRaydiumCpmmAmmConfig,
+    AccountRaydiumCpmmPoolState,
+    NonceAccount,
+    TokenAccount,
+    BlockMeta,
+}
+
 /// QUIC服务器和客户端之间传输的事件消息（自描述格式）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventMessage {
@@ -23,9 +96,63 @@ pub struct EventMessage {
     pub completion_time: u64,        // 3. 交易完成时间(到用户)
     pub client_processing_start: Option<u64>, // 客户端开始处理时间
     pub client_processing_end: Option<u64>,   // 客户端处理完成时间
+    // Bincode 按字段顺序编码，以下字段为次版本 1 追加，新字段只能继续追加在末尾
+    #[serde(default)]
+    pub dictionary_id: Option<u32>,   // 压缩使用的 zstd 字典编号
+    #[serde(default)]
+    pub sequence: u64,                // 流内单调递增的序列号（0 表示未分配）
+    // 自定义编解码器编号由帧头携带，不参与序列化
+    #[serde(skip)]
+    pub codec_id: Option<u8>,         // 压缩使用的注册编解码器编号（None 表示按 compression_format）
+}
+
+/// 次版本 0 的 Bincode 布局（没有次版本 1 追加的字段）
+#[derive(Deserialize)]
+struct EventMessageV0 {
+    event_id: String,
+    event_type: EventType,
+    data: Vec<u8>,
+    timestamp: u64,
+    serialization_format: SerializationProtocol,
+    compression_format: CompressionLevel,
+    is_compressed: bool,
+    original_size: Option<usize>,
+    grpc_arrival_time: u64,
+    parsing_time: u64,
+    completion_time: u64,
+    client_processing_start: Option<u64>,
+    client_processing_end: Option<u64>,
+}
+
+impl From<EventMessageV0> for EventMessage {
+    fn from(v0: EventMessageV0) -> Self {
+        Self {
+            event_id: v0.event_id,
+            event_type: v0.event_type,
+            data: v0.data,
+            timestamp: v0.timestamp,
+            serialization_format: v0.serialization_format,
+            compression_format: v0.compression_format,
+            is_compressed: v0.is_compressed,
+            original_size: v0.original_size,
+            grpc_arrival_time: v0.grpc_arrival_time,
+            parsing_time: v0.parsing_time,
+            completion_time: v0.completion_time,
+            client_processing_start: v0.client_processing_start,
+            client_processing_end: v0.client_processing_end,
+            dictionary_id: None,
+            sequence: 0,
+            codec_id: None,
+        }
+    }
 }
 
 impl EventMessage {
+    /// 按次版本 0 的布局解码 Bincode 数据
+    pub(crate) fn from_bincode_v0(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize::<EventMessageV0>(bytes).map(Self::from)
+    }
+
     pub fn new(
         event_type: EventType,
         data: Vec<u8>,
@@ -33,57 +160,16 @@ impl EventMessage {
         compression_format: CompressionLevel,
         is_compressed: bool,
     ) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let now = crate::unix_now().as_millis() as u64;
 
-        // 🚀 CRITICAL FIX: Actually compress the data when is_compressed=true
+        // 压缩失败或压缩后不更小时保留原始数据
         let (final_data, final_original_size, final_is_compressed) = if is_compressed && compression_format != CompressionLevel::None {
-            let original_size = data.len();
-            match compression_format {
-                CompressionLevel::LZ4Fast | CompressionLevel::LZ4High => {
-                    match lz4::block::compress(&data, None, false) {
-                        Ok(compressed_data) => {
-                            if compressed_data.len() < data.len() {
-                                // Compression successful
-                                (compressed_data, Some(original_size), true)
-                            } else {
-                                // Compression not beneficial, store uncompressed
-                                (data, None, false)
-                            }
-                        }
-                        Err(_) => {
-                            // Compression failed, store uncompressed  
-                            (data, None, false)
-                        }
-                    }
+            match compress_data(&data, compression_format) {
+                Ok(compressed_data) if compressed_data.len() < data.len() => {
+                    let original_size = data.len();
+                    (compressed_data, Some(original_size), true)
                 }
-                CompressionLevel::ZstdFast | CompressionLevel::ZstdMedium | CompressionLevel::ZstdHigh | CompressionLevel::ZstdMax => {
-                    let compression_level = match compression_format {
-                        CompressionLevel::ZstdFast => 1,
-                        CompressionLevel::ZstdMedium => 6,
-                        CompressionLevel::ZstdHigh => 15,
-                        CompressionLevel::ZstdMax => 22,
-                        _ => 1,
-                    };
-                    match zstd::encode_all(&data[..], compression_level) {
-                        Ok(compressed_data) => {
-                            if compressed_data.len() < data.len() {
-                                // Compression successful
-                                (compressed_data, Some(original_size), true)
-                            } else {
-                                // Compression not beneficial, store uncompressed
-                                (data, None, false)
-                            }
-                        }
-                        Err(_) => {
-                            // Compression failed, store uncompressed  
-                            (data, None, false)
-                        }
-                    }
-                }
-                CompressionLevel::None => (data, None, false),
+                _ => (data, None, false),
             }
         } else {
             // No compression requested
@@ -104,47 +190,90 @@ impl EventMessage {
             completion_time: 0,
             client_processing_start: None,
             client_processing_end: None,
+            dictionary_id: None,
+            sequence: 0,
+            codec_id: None,
+        }
+    }
+
+    /// 使用 zstd 字典压缩数据创建事件消息
+    ///
+    /// 只有 zstd 压缩级别会使用字典，其余级别等同于 `new`；
+    /// 字典不存在或压缩后不更小时保留原始数据。
+    pub fn new_with_dictionary(
+        event_type: EventType,
+        data: Vec<u8>,
+        serialization_format: SerializationProtocol,
+        compression_format: CompressionLevel,
+        dictionaries: &DictionaryStore,
+        dictionary_id: u32,
+    ) -> Self {
+        if CompressionType::from(compression_format) != CompressionType::Zstd {
+            return Self::new(event_type, data, serialization_format, compression_format, true);
+        }
+
+        let mut message = Self::new(event_type, Vec::new(), serialization_format, compression_format, false);
+        match dictionaries.compress(dictionary_id, &data, compression_format.codec_level()) {
+            Ok(compressed_data) if compressed_data.len() < data.len() => {
+                message.original_size = Some(data.len());
+                message.data = compressed_data;
+                message.is_compressed = true;
+                message.dictionary_id = Some(dictionary_id);
+            }
+            _ => message.data = data,
+        }
+        message
+    }
+
+    /// 使用已注册的编解码器压缩数据创建事件消息
+    ///
+    /// 帧头中的编解码器编号为 `codec.id()`，接收端需在 `CodecRegistry` 中注册同一编解码器，
+    /// 并通过 `get_decompressed_data_with_codecs` 解压；压缩失败或压缩后不更小时保留原始数据。
+    pub fn new_with_codec(
+        event_type: EventType,
+        data: Vec<u8>,
+        serialization_format: SerializationProtocol,
+        codec: &dyn Codec,
+        level: i32,
+    ) -> Self {
+        let mut message = Self::new(event_type, Vec::new(), serialization_format, CompressionLevel::None, false);
+        message.codec_id = Some(codec.id());
+        let mut compressed_data = Vec::with_capacity(codec.max_compressed_len(data.len()));
+        match codec.compress_into(&data, level, &mut compressed_data) {
+            Ok(()) if compressed_data.len() < data.len() => {
+                message.original_size = Some(data.len());
+                message.data = compressed_data;
+                message.is_compressed = true;
+            }
+            _ => message.data = data,
         }
+        message
+    }
+
+    /// 设置流内序列号
+    pub fn set_sequence(&mut self, sequence: u64) {
+        self.sequence = sequence;
     }
 
     /// 设置性能时间戳
     pub fn set_grpc_arrival_time(&mut self) {
-        self.grpc_arrival_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        self.grpc_arrival_time = crate::unix_now().as_micros() as u64;
     }
 
     pub fn set_parsing_time(&mut self) {
-        self.parsing_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        self.parsing_time = crate::unix_now().as_micros() as u64;
     }
 
     pub fn set_completion_time(&mut self) {
-        self.completion_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        self.completion_time = crate::unix_now().as_micros() as u64;
     }
 
     pub fn set_client_processing_start(&mut self) {
-        self.client_processing_start = Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_micros() as u64,
-        );
+        self.client_processing_start = Some(crate::unix_now().as_micros() as u64);
     }
 
     pub fn set_client_processing_end(&mut self) {
-        self.client_processing_end = Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_micros() as u64,
-        );
+        self.client_processing_end = Some(crate::unix_now().as_micros() as u64);
     }
 
     /// 获取总处理时间（微秒）
@@ -158,11 +287,7 @@ impl EventMessage {
 
     /// 获取从grpc到达到客户端完成的总时间（微秒）
     pub fn get_end_to_end_latency(&self) -> Option<u64> {
-        if let Some(end) = self.client_processing_end {
-            Some(end - self.grpc_arrival_time)
-        } else {
-            None
-        }
+        self.client_processing_end.map(|end| end - self.grpc_arrival_time)
     }
 
     /// 获取服务器端处理时间（微秒）
@@ -181,13 +306,13 @@ impl EventMessage {
 
     /// 获取原始大小或当前大小
     pub fn get_original_size(&self) -> usize {
-        self.original_size.unwrap_or_else(|| self.data.len())
+        self.original_size.unwrap_or(self.data.len())
     }
 
     /// 获取压缩比率（如果有压缩）
     pub fn get_compression_ratio(&self) -> Option<f64> {
-        if self.is_compressed && self.original_size.is_some() {
-            let original = self.original_size.unwrap() as f64;
+        if let (true, Some(original_size)) = (self.is_compressed, self.original_size) {
+            let original = original_size as f64;
             let compressed = self.data.len() as f64;
             Some(compressed / original)
         } else {
@@ -196,23 +321,67 @@ impl EventMessage {
     }
 
     /// 获取解压后的数据
-    pub fn get_decompressed_data(&self) -> Result<Vec<u8>, anyhow::Error> {
-        if self.is_compressed {
-            match self.compression_format {
-                CompressionLevel::None => Ok(self.data.clone()),
-                CompressionLevel::LZ4Fast | CompressionLevel::LZ4High => {
-                    lz4::block::decompress(&self.data, self.original_size.map(|s| s as i32))
-                        .map_err(|e| anyhow::anyhow!("LZ4 decompression failed: {}", e))
-                }
-                CompressionLevel::ZstdFast | CompressionLevel::ZstdMedium | CompressionLevel::ZstdHigh | CompressionLevel::ZstdMax => {
-                    zstd::decode_all(&self.data[..])
-                        .map_err(|e| anyhow::anyhow!("Zstd decompression failed: {}", e))
-                }
+    pub fn get_decompressed_data(&self) -> FzResult<Vec<u8>> {
+        self.get_decompressed_data_with_limit(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// 获取解压后的数据，解压后超过 `max_size` 字节时返回错误
+    pub fn get_decompressed_data_with_limit(&self, max_size: usize) -> FzResult<Vec<u8>> {
+        if !self.is_compressed {
+            return Ok(self.data.clone());
+        }
+        if let Some(dictionary_id) = self.dictionary_id {
+            return Err(FzStreamError::Decompression(format!(
+                "message was compressed with dictionary {}, use get_decompressed_data_with_dictionaries",
+                dictionary_id
+            )));
+        }
+
+        let decompressed = match self.codec_id {
+            Some(codec_id) => CodecRegistry::global().decompress(codec_id, &self.data, max_size)?,
+            None => decompress_data_with_limit(&self.data, self.compression_format, max_size)?,
+        };
+        self.check_original_size(decompressed)
+    }
+
+    /// 获取解压后的数据，大小上限为配置的 `max_decompressed_size`
+    pub fn get_decompressed_data_with_config(&self, config: &CompressionConfig) -> FzResult<Vec<u8>> {
+        self.get_decompressed_data_with_limit(config.max_decompressed_size)
+    }
+
+    /// 获取解压后的数据，消息使用字典压缩时从 `dictionaries` 中查找字典
+    pub fn get_decompressed_data_with_dictionaries(&self, dictionaries: &DictionaryStore) -> FzResult<Vec<u8>> {
+        match self.dictionary_id {
+            Some(dictionary_id) if self.is_compressed => {
+                let decompressed = dictionaries.decompress(dictionary_id, &self.data, DEFAULT_MAX_DECOMPRESSED_SIZE)?;
+                self.check_original_size(decompressed)
             }
-        } else {
-            Ok(self.data.clone())
+            _ => self.get_decompressed_data(),
         }
     }
+
+    /// 获取解压后的数据，从 `codecs` 中查找消息使用的编解码器
+    pub fn get_decompressed_data_with_codecs(&self, codecs: &CodecRegistry) -> FzResult<Vec<u8>> {
+        if !self.is_compressed || self.dictionary_id.is_some() {
+            return self.get_decompressed_data();
+        }
+        let codec_id = self.codec_id.unwrap_or_else(|| self.compression_format.codec_id());
+        let decompressed = codecs.decompress(codec_id, &self.data, DEFAULT_MAX_DECOMPRESSED_SIZE)?;
+        self.check_original_size(decompressed)
+    }
+
+    fn check_original_size(&self, decompressed: Vec<u8>) -> FzResult<Vec<u8>> {
+        if let Some(original_size) = self.original_size {
+            if decompressed.len() != original_size {
+                return Err(FzStreamError::Decompression(format!(
+                    "decompressed {} bytes, expected {}",
+                    decompressed.len(),
+                    original_size
+                )));
+            }
+        }
+        Ok(decompressed)
+    }
     
     /// 获取客户端处理时间（毫秒）
     pub fn client_processing_time_ms(&self) -> Option<u64> {
@@ -364,8 +533,11 @@ impl EventTypeFilter {
     pub fn get_allowed_types(&self) -> Vec<EventType> {
         if self.allow_all {
             // 如果允许所有，返回除了blocked之外的所有标准类型
-            // 这里可能需要根据实际的EventType enum来定义
-            Vec::new() // 暂时返回空，实际应该返回所有标准类型
+            ALL_EVENT_TYPES
+                .iter()
+                .filter(|t| !self.blocked_types.contains(t))
+                .cloned()
+                .collect()
         } else {
             self.allowed_types.clone()
         }
@@ -0,0 +1,188 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::codec::{CodecRegistry, CODEC_ID_LZ4, CODEC_ID_NONE, CODEC_ID_ZSTD};
+use crate::dictionary::DictionaryStore;
+use crate::error::{FzResult, FzStreamError};
+use crate::wire::{WIRE_VERSION_MAJOR, WIRE_VERSION_MINOR};
+use crate::{CompressionType, CustomSettings, SerializationProtocol};
+
+/// 帧协议版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl ProtocolVersion {
+    pub const CURRENT: ProtocolVersion = ProtocolVersion {
+        major: WIRE_VERSION_MAJOR,
+        minor: WIRE_VERSION_MINOR,
+    };
+
+    pub fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// 握手能力声明
+///
+/// 客户端在 `AuthMessage` 中携带；服务器用同一结构描述自身能力，
+/// 列表顺序即偏好顺序。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub protocol_versions: Vec<ProtocolVersion>,
+    /// 支持的序列化协议，包含 `Auto` 或为空时表示接受对方的任意选择
+    pub serializations: Vec<SerializationProtocol>,
+    /// 支持的编解码器编号（`CODEC_ID_NONE` 总是隐含支持）
+    pub codecs: Vec<u8>,
+    /// 已加载的 zstd 字典编号
+    pub dictionaries: Vec<u32>,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hello {
+    /// 当前版本、内置序列化协议和全局注册表中的内置编解码器
+    pub fn new() -> Self {
+        let registry = CodecRegistry::global();
+        Self {
+            protocol_versions: vec![ProtocolVersion::CURRENT],
+            serializations: vec![SerializationProtocol::Bincode, SerializationProtocol::JSON],
+            codecs: [CODEC_ID_ZSTD, CODEC_ID_LZ4, CODEC_ID_NONE]
+                .into_iter()
+                .filter(|&id| registry.contains(id))
+                .collect(),
+            dictionaries: Vec::new(),
+        }
+    }
+
+    /// 根据客户端设置生成能力声明
+    ///
+    /// 未开启 `adaptive_protocol` 且指定了具体协议时，只声明该协议和对应编解码器，
+    /// 避免服务器选择其他组合。
+    pub fn from_settings(settings: &CustomSettings) -> Self {
+        let mut hello = Self::new();
+        if settings.adaptive_protocol || settings.serialization_protocol == SerializationProtocol::Auto {
+            return hello;
+        }
+
+        hello.serializations = vec![settings.serialization_protocol];
+        let codec_id = settings.compression_level.codec_id();
+        hello.codecs = if codec_id == CODEC_ID_NONE {
+            vec![CODEC_ID_NONE]
+        } else {
+            vec![codec_id, CODEC_ID_NONE]
+        };
+        hello
+    }
+
+    /// 声明字典存储中的所有字典
+    pub fn with_dictionaries(mut self, dictionaries: &DictionaryStore) -> Self {
+        self.dictionaries = dictionaries.ids();
+        self.dictionaries.sort_unstable();
+        self
+    }
+
+    fn accepts_any_serialization(&self) -> bool {
+        self.serializations.is_empty() || self.serializations.contains(&SerializationProtocol::Auto)
+    }
+
+    fn supports_codec(&self, codec_id: u8) -> bool {
+        codec_id == CODEC_ID_NONE || self.codecs.contains(&codec_id)
+    }
+
+    /// 某个主版本下支持的最高次版本
+    fn max_minor(&self, major: u8) -> Option<u8> {
+        self.protocol_versions
+            .iter()
+            .filter(|version| version.major == major)
+            .map(|version| version.minor)
+            .max()
+    }
+}
+
+/// 服务器的协商结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Welcome {
+    pub protocol_version: ProtocolVersion,
+    pub serialization: SerializationProtocol,
+    pub codec_id: u8,
+    /// 双方都已加载的字典（仅在选择 zstd 时非空）
+    pub dictionaries: Vec<u32>,
+}
+
+impl Welcome {
+    /// 协商出的压缩类型（自定义编解码器返回 None）
+    pub fn compression_type(&self) -> Option<CompressionType> {
+        CompressionType::from_wire_id(self.codec_id)
+    }
+}
+
+/// 根据双方能力选出最佳组合
+///
+/// 结果只取决于两份能力声明，与调用方无关：
+/// - 版本：双方共有的最高主版本，次版本取双方在该主版本下最高次版本的较小者
+/// - 序列化协议和编解码器：按服务器偏好顺序选第一个客户端支持的；
+///   没有共同编解码器时退回不压缩
+/// - 字典：选择 zstd 时取双方字典编号的交集（升序）
+pub fn negotiate(client: &Hello, server: &Hello) -> FzResult<Welcome> {
+    let protocol_version = server
+        .protocol_versions
+        .iter()
+        .map(|version| version.major)
+        .filter_map(|major| {
+            let minor = server.max_minor(major)?.min(client.max_minor(major)?);
+            Some(ProtocolVersion::new(major, minor))
+        })
+        .max()
+        .ok_or_else(|| FzStreamError::Negotiation("no common protocol version".to_string()))?;
+
+    let server_serializations: Vec<SerializationProtocol> = if server.accepts_any_serialization() {
+        vec![SerializationProtocol::Bincode, SerializationProtocol::JSON]
+    } else {
+        server.serializations.clone()
+    };
+    let serialization = server_serializations
+        .into_iter()
+        .filter(|&protocol| protocol != SerializationProtocol::Auto)
+        .find(|protocol| client.accepts_any_serialization() || client.serializations.contains(protocol))
+        .ok_or_else(|| FzStreamError::Negotiation("no common serialization protocol".to_string()))?;
+
+    let codec_id = server
+        .codecs
+        .iter()
+        .copied()
+        .find(|&id| client.supports_codec(id))
+        .unwrap_or(CODEC_ID_NONE);
+
+    let mut dictionaries: Vec<u32> = if codec_id == CODEC_ID_ZSTD {
+        server
+            .dictionaries
+            .iter()
+            .copied()
+            .filter(|id| client.dictionaries.contains(id))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    dictionaries.sort_unstable();
+    dictionaries.dedup();
+
+    Ok(Welcome {
+        protocol_version,
+        serialization,
+        codec_id,
+        dictionaries,
+    })
+}
@@ -0,0 +1,520 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::{hmac, signature};
+
+use crate::auth::TokenClaims;
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+use crate::keyring::Keyring;
+use crate::quota::ClientQuota;
+use crate::ServerConfig;
+
+/// 默认允许的时钟偏差（秒）
+pub const DEFAULT_JWT_LEEWAY_SECS: u64 = 60;
+
+/// 支持的 JWT 签名算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JwtAlgorithm {
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+impl JwtAlgorithm {
+    /// JWT 头部 `alg` 字段的取值
+    pub fn name(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::HS256 => "HS256",
+            JwtAlgorithm::RS256 => "RS256",
+            JwtAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "HS256" => Some(JwtAlgorithm::HS256),
+            "RS256" => Some(JwtAlgorithm::RS256),
+            "EdDSA" => Some(JwtAlgorithm::EdDSA),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// JWT 头部
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtHeader {
+    pub alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+}
+
+impl JwtHeader {
+    /// 解析令牌的头部（不验证签名）
+    pub fn decode(token: &str) -> FzResult<Self> {
+        let header_b64 = token.split('.').next().unwrap_or_default();
+        decode_json(header_b64)
+    }
+}
+
+/// JWT 负载
+///
+/// 权限可以放在 `permissions` 数组中，也可以按 OAuth 习惯放在空格分隔的 `scope` 中。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JwtClaims {
+    pub sub: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_info: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<ClientQuota>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl From<JwtClaims> for TokenClaims {
+    fn from(claims: JwtClaims) -> Self {
+        let mut permissions = claims.permissions;
+        for scope in claims.scope.iter().flat_map(|scope| scope.split_whitespace()) {
+            if !permissions.iter().any(|permission| permission == scope) {
+                permissions.push(scope.to_string());
+            }
+        }
+
+        TokenClaims {
+            user_id: claims.sub,
+            permissions,
+            issued_at: claims.iat.unwrap_or(0),
+            expires_at: claims.exp.unwrap_or(u64::MAX),
+            client_info: claims.client_info,
+            quota: claims.quota,
+            token_id: claims.jti,
+            key_id: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum JwtKeyMaterial {
+    Hmac(hmac::Key),
+    Rsa(Vec<u8>),
+    Ed25519(Vec<u8>),
+}
+
+/// JWT 验证密钥
+#[derive(Clone)]
+pub struct JwtKey {
+    kid: Option<String>,
+    algorithm: JwtAlgorithm,
+    material: JwtKeyMaterial,
+}
+
+impl fmt::Debug for JwtKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtKey")
+            .field("kid", &self.kid)
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+impl JwtKey {
+    /// HS256 共享密钥
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self {
+            kid: None,
+            algorithm: JwtAlgorithm::HS256,
+            material: JwtKeyMaterial::Hmac(hmac::Key::new(hmac::HMAC_SHA256, secret)),
+        }
+    }
+
+    /// RS256 公钥（PKCS#1 DER 编码的 RSAPublicKey）
+    pub fn rs256_public_key(der: Vec<u8>) -> Self {
+        Self {
+            kid: None,
+            algorithm: JwtAlgorithm::RS256,
+            material: JwtKeyMaterial::Rsa(der),
+        }
+    }
+
+    /// EdDSA 公钥（32 字节 Ed25519 公钥）
+    pub fn ed25519_public_key(public_key: Vec<u8>) -> Self {
+        Self {
+            kid: None,
+            algorithm: JwtAlgorithm::EdDSA,
+            material: JwtKeyMaterial::Ed25519(public_key),
+        }
+    }
+
+    /// 设置密钥编号，令牌头部带 `kid` 时只匹配相同编号的密钥
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.algorithm
+    }
+
+    fn matches(&self, algorithm: JwtAlgorithm, kid: Option<&str>) -> bool {
+        self.algorithm == algorithm
+            && match (self.kid.as_deref(), kid) {
+                (Some(key_kid), Some(kid)) => key_kid == kid,
+                _ => true,
+            }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match &self.material {
+            JwtKeyMaterial::Hmac(key) => hmac::verify(key, message, signature).is_ok(),
+            JwtKeyMaterial::Rsa(der) => signature::UnparsedPublicKey::new(&signature::RSA_PKCS1_2048_8192_SHA256, der)
+                .verify(message, signature)
+                .is_ok(),
+            JwtKeyMaterial::Ed25519(public_key) => signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+                .verify(message, signature)
+                .is_ok(),
+        }
+    }
+}
+
+/// JWT 验证器
+///
+/// 校验签名后检查 `exp`、`nbf`、`iat`，三者都允许 `leeway_secs` 的时钟偏差。
+#[derive(Debug)]
+pub struct JwtVerifier {
+    keys: Vec<JwtKey>,
+    leeway_secs: u64,
+    require_exp: bool,
+}
+
+impl Default for JwtVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JwtVerifier {
+    /// 创建没有密钥的验证器（要求令牌携带 `exp`）
+    pub fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            leeway_secs: DEFAULT_JWT_LEEWAY_SECS,
+            require_exp: true,
+        }
+    }
+
+    /// 使用 HS256 共享密钥创建验证器
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self::new().with_key(JwtKey::hs256(secret))
+    }
+
+    pub fn with_key(mut self, key: JwtKey) -> Self {
+        self.add_key(key);
+        self
+    }
+
+    pub fn add_key(&mut self, key: JwtKey) {
+        self.keys.push(key);
+    }
+
+    /// 设置允许的时钟偏差（秒）
+    pub fn with_leeway(mut self, leeway_secs: u64) -> Self {
+        self.leeway_secs = leeway_secs;
+        self
+    }
+
+    /// 是否拒绝不带 `exp` 的令牌
+    pub fn with_require_exp(mut self, require_exp: bool) -> Self {
+        self.require_exp = require_exp;
+        self
+    }
+
+    pub fn leeway_secs(&self) -> u64 {
+        self.leeway_secs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// 验证令牌并返回声明
+    pub fn verify(&self, token: &str) -> FzResult<TokenClaims> {
+        let now = crate::unix_now().as_secs();
+        self.verify_at(token, now)
+    }
+
+    /// 以指定时间（Unix 秒）验证令牌
+    pub fn verify_at(&self, token: &str, now: u64) -> FzResult<TokenClaims> {
+        let mut parts = token.split('.');
+        let (header_b64, payload_b64, signature_b64) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(payload), Some(signature), None) => (header, payload, signature),
+            _ => return Err(malformed("JWT must have three parts")),
+        };
+
+        let header: JwtHeader = decode_json(header_b64)?;
+        let algorithm = JwtAlgorithm::from_name(&header.alg).ok_or_else(|| {
+            FzStreamError::auth(AuthErrorCode::InvalidToken, format!("unsupported JWT algorithm: {}", header.alg))
+        })?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature_b64)
+            .map_err(|_| malformed("invalid JWT signature encoding"))?;
+
+        let signing_input = &token.as_bytes()[..header_b64.len() + 1 + payload_b64.len()];
+        let mut candidates = self
+            .keys
+            .iter()
+            .filter(|key| key.matches(algorithm, header.kid.as_deref()))
+            .peekable();
+        if candidates.peek().is_none() {
+            return Err(FzStreamError::auth(
+                AuthErrorCode::InvalidToken,
+                format!("no {} key configured for JWT", algorithm),
+            ));
+        }
+        let key = candidates
+            .find(|key| key.verify(signing_input, &signature))
+            .ok_or_else(|| FzStreamError::auth(AuthErrorCode::InvalidToken, "invalid JWT signature"))?;
+
+        let claims: JwtClaims = decode_json(payload_b64)?;
+        self.check_times(&claims, now)?;
+        if claims.sub.is_empty() {
+            return Err(malformed("JWT has no subject"));
+        }
+        // 使用验证通过的密钥的编号，头部的 `kid` 由令牌持有者提供，不可信
+        let mut claims: TokenClaims = claims.into();
+        claims.key_id = key.kid.clone();
+        Ok(claims)
+    }
+
+    fn check_times(&self, claims: &JwtClaims, now: u64) -> FzResult<()> {
+        match claims.exp {
+            Some(exp) if now > exp.saturating_add(self.leeway_secs) => {
+                return Err(FzStreamError::auth(AuthErrorCode::ExpiredToken, "JWT has expired"));
+            }
+            None if self.require_exp => return Err(malformed("JWT has no exp claim")),
+            _ => {}
+        }
+
+        let latest_allowed = now.saturating_add(self.leeway_secs);
+        if claims.nbf.is_some_and(|nbf| nbf > latest_allowed) {
+            return Err(FzStreamError::auth(AuthErrorCode::TokenNotYetValid, "JWT is not valid yet (nbf)"));
+        }
+        if claims.iat.is_some_and(|iat| iat > latest_allowed) {
+            return Err(FzStreamError::auth(AuthErrorCode::TokenNotYetValid, "JWT was issued in the future (iat)"));
+        }
+        Ok(())
+    }
+}
+
+fn malformed(message: &str) -> FzStreamError {
+    FzStreamError::auth(AuthErrorCode::MalformedToken, message)
+}
+
+fn decode_json<T: serde::de::DeserializeOwned>(segment: &str) -> FzResult<T> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| malformed("invalid JWT base64 encoding"))?;
+    serde_json::from_slice(&bytes).map_err(|e| malformed(&format!("invalid JWT JSON: {}", e)))
+}
+
+impl From<&TokenClaims> for JwtClaims {
+    fn from(claims: &TokenClaims) -> Self {
+        JwtClaims {
+            sub: claims.user_id.clone(),
+            exp: (claims.expires_at != u64::MAX).then_some(claims.expires_at),
+            nbf: None,
+            iat: Some(claims.issued_at),
+            permissions: claims.permissions.clone(),
+            scope: None,
+            client_info: claims.client_info.clone(),
+            quota: claims.quota,
+            jti: claims.token_id.clone(),
+        }
+    }
+}
+
+enum JwtSigningKey {
+    Hmac(hmac::Key),
+    Rsa(signature::RsaKeyPair),
+    Ed25519(signature::Ed25519KeyPair),
+}
+
+/// 令牌签发器
+///
+/// 签发的 JWT 可被使用对应密钥的 `JwtVerifier` 验证。克隆时共享同一私钥。
+#[derive(Clone)]
+pub struct TokenIssuer {
+    kid: Option<String>,
+    algorithm: JwtAlgorithm,
+    key: Arc<JwtSigningKey>,
+}
+
+impl fmt::Debug for TokenIssuer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenIssuer")
+            .field("kid", &self.kid)
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+impl TokenIssuer {
+    /// 使用 HS256 共享密钥签发
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self {
+            kid: None,
+            algorithm: JwtAlgorithm::HS256,
+            key: Arc::new(JwtSigningKey::Hmac(hmac::Key::new(hmac::HMAC_SHA256, secret))),
+        }
+    }
+
+    /// 使用 PKCS#8 DER 编码的 RSA 私钥签发 RS256 令牌
+    pub fn rs256_from_pkcs8(pkcs8: &[u8]) -> FzResult<Self> {
+        let key_pair = signature::RsaKeyPair::from_pkcs8(pkcs8)
+            .map_err(|e| FzStreamError::Config(format!("invalid RSA private key: {}", e)))?;
+        Ok(Self {
+            kid: None,
+            algorithm: JwtAlgorithm::RS256,
+            key: Arc::new(JwtSigningKey::Rsa(key_pair)),
+        })
+    }
+
+    /// 使用 PKCS#8 DER 编码的 Ed25519 私钥签发 EdDSA 令牌
+    pub fn ed25519_from_pkcs8(pkcs8: &[u8]) -> FzResult<Self> {
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .map_err(|e| FzStreamError::Config(format!("invalid Ed25519 private key: {}", e)))?;
+        Ok(Self {
+            kid: None,
+            algorithm: JwtAlgorithm::EdDSA,
+            key: Arc::new(JwtSigningKey::Ed25519(key_pair)),
+        })
+    }
+
+    /// 根据服务器配置创建签发器
+    ///
+    /// 配置了 `signing_keys` 时使用 `Keyring::from_server_config` 选出的主密钥（已停用时返回 None），
+    /// 否则使用 `auth_secret_key`，都未配置时返回 None。
+    pub fn from_server_config(config: &ServerConfig) -> Option<Self> {
+        match Keyring::from_server_config(config) {
+            Some(keyring) => keyring.primary_issuer().ok(),
+            None => config
+                .auth_secret_key
+                .as_ref()
+                .map(|secret| Self::hs256(secret.as_bytes())),
+        }
+    }
+
+    /// 设置写入令牌头部的密钥编号
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.algorithm
+    }
+
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// 验证该签发器所签令牌的密钥
+    pub fn verification_key(&self) -> JwtKey {
+        let key = match self.key.as_ref() {
+            JwtSigningKey::Hmac(key) => JwtKey {
+                kid: None,
+                algorithm: JwtAlgorithm::HS256,
+                material: JwtKeyMaterial::Hmac(key.clone()),
+            },
+            JwtSigningKey::Rsa(key_pair) => JwtKey::rs256_public_key(key_pair.public().as_ref().to_vec()),
+            JwtSigningKey::Ed25519(key_pair) => {
+                JwtKey::ed25519_public_key(signature::KeyPair::public_key(key_pair).as_ref().to_vec())
+            }
+        };
+        match &self.kid {
+            Some(kid) => key.with_kid(kid.clone()),
+            None => key,
+        }
+    }
+
+    /// 签发有效期为 `lifetime` 的令牌
+    pub fn issue(
+        &self,
+        user_id: impl Into<String>,
+        permissions: Vec<String>,
+        lifetime: Duration,
+        client_info: Option<String>,
+    ) -> FzResult<String> {
+        let issued_at = crate::unix_now().as_secs();
+
+        let claims = TokenClaims {
+            user_id: user_id.into(),
+            permissions,
+            issued_at,
+            expires_at: issued_at.saturating_add(lifetime.as_secs()),
+            client_info,
+            quota: None,
+            token_id: Some(uuid::Uuid::new_v4().simple().to_string()),
+            key_id: None,
+        };
+        self.sign_claims(&claims)
+    }
+
+    /// 对给定声明签名
+    pub fn sign_claims(&self, claims: &TokenClaims) -> FzResult<String> {
+        if claims.user_id.is_empty() {
+            return Err(FzStreamError::Signing("token user_id must not be empty".to_string()));
+        }
+
+        let header = JwtHeader {
+            alg: self.algorithm.name().to_string(),
+            typ: Some("JWT".to_string()),
+            kid: self.kid.clone(),
+        };
+        let header_json = serde_json::to_vec(&header)?;
+        let claims_json = serde_json::to_vec(&JwtClaims::from(claims))?;
+
+        let signing_input = format!("{}.{}", URL_SAFE_NO_PAD.encode(header_json), URL_SAFE_NO_PAD.encode(claims_json));
+        let signature = self.sign(signing_input.as_bytes())?;
+        Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    fn sign(&self, message: &[u8]) -> FzResult<Vec<u8>> {
+        match self.key.as_ref() {
+            JwtSigningKey::Hmac(key) => Ok(hmac::sign(key, message).as_ref().to_vec()),
+            JwtSigningKey::Rsa(key_pair) => {
+                let mut signature = vec![0; key_pair.public().modulus_len()];
+                key_pair
+                    .sign(&signature::RSA_PKCS1_SHA256, &SystemRandom::new(), message, &mut signature)
+                    .map_err(|_| FzStreamError::Signing("RSA signature could not be computed".to_string()))?;
+                Ok(signature)
+            }
+            JwtSigningKey::Ed25519(key_pair) => Ok(key_pair.sign(message).as_ref().to_vec()),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use serde::{Serialize, Deserialize};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::auth::TokenClaims;
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+use crate::jwt::{JwtHeader, JwtKey, JwtVerifier, TokenIssuer, DEFAULT_JWT_LEEWAY_SECS};
+use crate::ServerConfig;
+
+/// 从 `auth_secret_key` 导入密钥环时使用的密钥编号
+pub const LEGACY_SIGNING_KEY_ID: &str = "default";
+
+/// 配置文件中的 HS256 签名密钥
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningKeyConfig {
+    pub kid: String,
+    pub secret: String,
+    /// 是否用于签发新令牌
+    #[serde(default)]
+    pub primary: bool,
+    /// 停用时间（Unix 秒），之后不再接受该密钥签名的令牌
+    #[serde(default)]
+    pub retire_at: Option<u64>,
+}
+
+/// 密钥环中的密钥
+///
+/// 持有私钥时可以签发令牌，否则只用于验证。
+#[derive(Debug)]
+pub struct SigningKey {
+    kid: String,
+    issuer: Option<TokenIssuer>,
+    verification_key: JwtKey,
+    retire_at: Option<u64>,
+}
+
+impl SigningKey {
+    /// 由签发器创建，验证密钥从签发器导出
+    pub fn new(kid: impl Into<String>, issuer: TokenIssuer) -> Self {
+        let kid = kid.into();
+        let issuer = issuer.with_kid(kid.clone());
+        Self {
+            verification_key: issuer.verification_key(),
+            issuer: Some(issuer),
+            kid,
+            retire_at: None,
+        }
+    }
+
+    /// HS256 共享密钥
+    pub fn hs256(kid: impl Into<String>, secret: &[u8]) -> Self {
+        Self::new(kid, TokenIssuer::hs256(secret))
+    }
+
+    /// 只用于验证的密钥（例如只持有公钥）
+    pub fn verify_only(kid: impl Into<String>, key: JwtKey) -> Self {
+        let kid = kid.into();
+        Self {
+            verification_key: key.with_kid(kid.clone()),
+            issuer: None,
+            kid,
+            retire_at: None,
+        }
+    }
+
+    /// 设置停用时间（Unix 秒）
+    pub fn with_retire_at(mut self, retire_at: u64) -> Self {
+        self.retire_at = Some(retire_at);
+        self
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn retire_at(&self) -> Option<u64> {
+        self.retire_at
+    }
+
+    pub fn can_sign(&self) -> bool {
+        self.issuer.is_some()
+    }
+
+    pub fn is_retired_at(&self, now: u64) -> bool {
+        self.retire_at.is_some_and(|retire_at| now >= retire_at)
+    }
+}
+
+#[derive(Debug, Default)]
+struct KeyringState {
+    keys: Vec<SigningKey>,
+    primary: Option<String>,
+    /// 包含所有密钥的验证器，密钥变化时重建
+    verifier: JwtVerifier,
+}
+
+/// 签名密钥环
+///
+/// 主密钥用于签发，其余未停用的密钥继续用于验证，轮换时先添加新密钥并设为主密钥，
+/// 再给旧密钥设置停用时间（不早于旧令牌的过期时间）。
+/// 令牌头部带 `kid` 时只尝试同编号的密钥，不带时尝试所有未停用的密钥。
+#[derive(Debug)]
+pub struct Keyring {
+    state: RwLock<KeyringState>,
+    leeway_secs: u64,
+    require_exp: bool,
+}
+
+impl Default for Keyring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keyring {
+    pub fn new() -> Self {
+        let keyring = Self {
+            state: RwLock::new(KeyringState::default()),
+            leeway_secs: DEFAULT_JWT_LEEWAY_SECS,
+            require_exp: true,
+        };
+        keyring.rebuild_verifier(&mut keyring.state_mut());
+        keyring
+    }
+
+    /// 根据服务器配置创建（未配置 `signing_keys` 时返回 None）
+    ///
+    /// 同时配置了 `auth_secret_key` 时，它以 `default` 编号加入密钥环，继续接受旧令牌。
+    /// 没有标记为主密钥的配置时，第一个密钥作为主密钥。
+    pub fn from_server_config(config: &ServerConfig) -> Option<Self> {
+        if config.signing_keys.is_empty() {
+            return None;
+        }
+
+        let keyring = Self::new();
+        for key_config in &config.signing_keys {
+            let mut key = SigningKey::hs256(key_config.kid.clone(), key_config.secret.as_bytes());
+            if let Some(retire_at) = key_config.retire_at {
+                key = key.with_retire_at(retire_at);
+            }
+            keyring.add(key);
+        }
+        if let Some(secret) = &config.auth_secret_key {
+            if !config.signing_keys.iter().any(|key| key.kid == LEGACY_SIGNING_KEY_ID) {
+                keyring.add(SigningKey::hs256(LEGACY_SIGNING_KEY_ID, secret.as_bytes()));
+            }
+        }
+
+        let primary = config
+            .signing_keys
+            .iter()
+            .find(|key| key.primary)
+            .unwrap_or(&config.signing_keys[0]);
+        keyring.state_mut().primary = Some(primary.kid.clone());
+        Some(keyring)
+    }
+
+    /// 设置允许的时钟偏差（秒）
+    pub fn with_leeway(mut self, leeway_secs: u64) -> Self {
+        self.leeway_secs = leeway_secs;
+        self.rebuild_verifier(&mut self.state_mut());
+        self
+    }
+
+    /// 是否拒绝不带 `exp` 的令牌
+    pub fn with_require_exp(mut self, require_exp: bool) -> Self {
+        self.require_exp = require_exp;
+        self.rebuild_verifier(&mut self.state_mut());
+        self
+    }
+
+    /// 添加密钥（同编号的密钥会被替换），密钥环中还没有主密钥且该密钥可签发时设为主密钥
+    pub fn add(&self, key: SigningKey) {
+        let mut state = self.state_mut();
+        state.keys.retain(|existing| existing.kid != key.kid);
+        if state.primary.is_none() && key.can_sign() {
+            state.primary = Some(key.kid.clone());
+        }
+        state.keys.push(key);
+        self.rebuild_verifier(&mut state);
+    }
+
+    /// 设置用于签发的主密钥
+    pub fn set_primary(&self, kid: &str) -> FzResult<()> {
+        let mut state = self.state_mut();
+        let key = state
+            .keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .ok_or_else(|| FzStreamError::Config(format!("unknown signing key: {}", kid)))?;
+        if !key.can_sign() {
+            return Err(FzStreamError::Config(format!("signing key {} has no private key", kid)));
+        }
+        if key.is_retired_at(crate::unix_now().as_secs()) {
+            return Err(FzStreamError::Config(format!("signing key {} is retired", kid)));
+        }
+        state.primary = Some(kid.to_string());
+        Ok(())
+    }
+
+    pub fn primary(&self) -> Option<String> {
+        self.state().primary.clone()
+    }
+
+    /// 设置密钥的停用时间（Unix 秒），主密钥不能停用
+    pub fn retire(&self, kid: &str, retire_at: u64) -> FzResult<()> {
+        let mut state = self.state_mut();
+        if state.primary.as_deref() == Some(kid) {
+            return Err(FzStreamError::Config(format!("cannot retire primary signing key {}", kid)));
+        }
+        let key = state
+            .keys
+            .iter_mut()
+            .find(|key| key.kid == kid)
+            .ok_or_else(|| FzStreamError::Config(format!("unknown signing key: {}", kid)))?;
+        key.retire_at = Some(retire_at);
+        self.rebuild_verifier(&mut state);
+        Ok(())
+    }
+
+    /// 删除密钥
+    pub fn remove(&self, kid: &str) -> bool {
+        let mut state = self.state_mut();
+        let before = state.keys.len();
+        state.keys.retain(|key| key.kid != kid);
+        if state.primary.as_deref() == Some(kid) {
+            state.primary = None;
+        }
+        self.rebuild_verifier(&mut state);
+        state.keys.len() != before
+    }
+
+    /// 删除已停用的密钥，返回删除的数量
+    pub fn purge_retired(&self, now: u64) -> usize {
+        let mut state = self.state_mut();
+        let before = state.keys.len();
+        state.keys.retain(|key| !key.is_retired_at(now));
+        self.rebuild_verifier(&mut state);
+        before - state.keys.len()
+    }
+
+    /// 所有密钥编号
+    pub fn kids(&self) -> Vec<String> {
+        self.state().keys.iter().map(|key| key.kid.clone()).collect()
+    }
+
+    /// 使用主密钥签发有效期为 `lifetime` 的令牌
+    pub fn issue(
+        &self,
+        user_id: impl Into<String>,
+        permissions: Vec<String>,
+        lifetime: Duration,
+        client_info: Option<String>,
+    ) -> FzResult<String> {
+        self.with_primary(|issuer| issuer.issue(user_id, permissions, lifetime, client_info))
+    }
+
+    /// 使用主密钥对给定声明签名
+    pub fn sign_claims(&self, claims: &TokenClaims) -> FzResult<String> {
+        self.with_primary(|issuer| issuer.sign_claims(claims))
+    }
+
+    /// 主密钥的签发器（与密钥环共享私钥）
+    pub fn primary_issuer(&self) -> FzResult<TokenIssuer> {
+        self.with_primary(|issuer| Ok(issuer.clone()))
+    }
+
+    /// 验证令牌并返回声明
+    pub fn verify(&self, token: &str) -> FzResult<TokenClaims> {
+        self.verify_at(token, crate::unix_now().as_secs())
+    }
+
+    /// 以指定时间（Unix 秒）验证令牌
+    pub fn verify_at(&self, token: &str, now: u64) -> FzResult<TokenClaims> {
+        let header = JwtHeader::decode(token)?;
+        let state = self.state();
+        if let Some(kid) = header.kid.as_deref() {
+            match state.keys.iter().find(|key| key.kid == kid) {
+                Some(key) if key.is_retired_at(now) => {
+                    return Err(FzStreamError::auth(
+                        AuthErrorCode::InvalidToken,
+                        format!("JWT signing key {} has been retired", kid),
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    return Err(FzStreamError::auth(
+                        AuthErrorCode::InvalidToken,
+                        format!("unknown JWT signing key: {}", kid),
+                    ));
+                }
+            }
+        }
+
+        // 验证器包含已停用的密钥，按实际验证通过的密钥再检查一次
+        let claims = state.verifier.verify_at(token, now)?;
+        if let Some(kid) = claims.key_id.as_deref() {
+            if state.keys.iter().any(|key| key.kid == kid && key.is_retired_at(now)) {
+                return Err(FzStreamError::auth(
+                    AuthErrorCode::InvalidToken,
+                    format!("JWT signing key {} has been retired", kid),
+                ));
+            }
+        }
+        Ok(claims)
+    }
+
+    fn rebuild_verifier(&self, state: &mut KeyringState) {
+        let mut verifier = JwtVerifier::new()
+            .with_leeway(self.leeway_secs)
+            .with_require_exp(self.require_exp);
+        for key in &state.keys {
+            verifier.add_key(key.verification_key.clone());
+        }
+        state.verifier = verifier;
+    }
+
+    fn with_primary<T>(&self, f: impl FnOnce(&TokenIssuer) -> FzResult<T>) -> FzResult<T> {
+        let state = self.state();
+        let primary = state
+            .primary
+            .as_deref()
+            .ok_or_else(|| FzStreamError::Config("keyring has no primary signing key".to_string()))?;
+        let key = state
+            .keys
+            .iter()
+            .find(|key| key.kid == primary)
+            .ok_or_else(|| FzStreamError::Config(format!("unknown signing key: {}", primary)))?;
+        if key.is_retired_at(crate::unix_now().as_secs()) {
+            return Err(FzStreamError::Config(format!("primary signing key {} is retired", primary)));
+        }
+        match &key.issuer {
+            Some(issuer) => f(issuer),
+            None => Err(FzStreamError::Config(format!("signing key {} has no private key", primary))),
+        }
+    }
+
+    fn state(&self) -> std::sync::RwLockReadGuard<'_, KeyringState> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn state_mut(&self) -> std::sync::RwLockWriteGuard<'_, KeyringState> {
+        self.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
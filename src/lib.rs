@@ -3,10 +3,38 @@
 //! 这个库包含了 fz-stream-server 和 fz-stream-client 之间共享的数据结构和类型定义。
 
 pub mod events;
+pub mod batch;
+pub mod sequence;
+pub mod replay;
+pub mod control;
+pub mod handshake;
 pub mod auth;
+pub mod jwt;
+pub mod keyring;
+pub mod audit;
+pub mod credentials;
+pub mod api_key;
+pub mod session;
+pub mod permission;
+pub mod nonce;
+pub mod quota;
+pub mod revocation;
 pub mod config;
+pub mod config_loader;
 pub mod compression;
 pub mod compression_stats;
+pub mod compression_policy;
+pub mod codec;
+pub mod dictionary;
+pub mod wire;
+pub mod error;
+
+/// 当前 Unix 时间（系统时钟早于 1970 年时返回 0）
+pub(crate) fn unix_now() -> std::time::Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
 
 // 定义 match_event! 宏
 #[macro_export]
@@ -37,7 +65,28 @@ macro_rules! match_event {
 
 // Re-export main types
 pub use events::*;
+pub use batch::*;
+pub use sequence::*;
+pub use replay::*;
+pub use control::*;
+pub use handshake::*;
 pub use auth::*;
+pub use jwt::*;
+pub use keyring::*;
+pub use audit::*;
+pub use credentials::*;
+pub use api_key::*;
+pub use session::*;
+pub use permission::*;
+pub use nonce::*;
+pub use quota::*;
+pub use revocation::*;
 pub use config::*;
+pub use config_loader::*;
 pub use compression::*;
 pub use compression_stats::*;
+pub use compression_policy::*;
+pub use codec::*;
+pub use dictionary::*;
+pub use wire::*;
+pub use error::*;
@@ -0,0 +1,130 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::auth::AuthMessage;
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+
+/// 默认允许的认证消息时间偏差
+pub const DEFAULT_AUTH_TIMESTAMP_WINDOW: Duration = Duration::from_secs(30);
+/// 默认最多缓存的 nonce 数量
+pub const DEFAULT_MAX_CACHED_NONCES: usize = 100_000;
+
+#[derive(Debug, Default)]
+struct NonceCache {
+    seen: HashMap<(String, String), u64>, // (client_id, nonce) -> 过期时间（毫秒）
+    order: VecDeque<((String, String), u64)>,
+}
+
+/// 认证消息重放检查
+///
+/// 拒绝时间戳超出窗口的消息，并缓存窗口内见过的 `(client_id, nonce)`，
+/// 同一 nonce 再次出现时拒绝。缓存已满时拒绝新的 nonce（`ReplayCacheFull`），
+/// 不会提前淘汰仍在窗口内的记录，否则攻击者可以先刷满缓存再重放。
+#[derive(Debug)]
+pub struct AuthReplayGuard {
+    window: Duration,
+    max_entries: usize,
+    require_nonce: bool,
+    cache: Mutex<NonceCache>,
+}
+
+impl Default for AuthReplayGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUTH_TIMESTAMP_WINDOW)
+    }
+}
+
+impl AuthReplayGuard {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            max_entries: DEFAULT_MAX_CACHED_NONCES,
+            require_nonce: true,
+            cache: Mutex::new(NonceCache::default()),
+        }
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// 是否拒绝不带 nonce 的消息（兼容旧客户端时可关闭，只检查时间戳）
+    pub fn with_require_nonce(mut self, require_nonce: bool) -> Self {
+        self.require_nonce = require_nonce;
+        self
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// 检查认证消息，通过后记录其 nonce
+    pub fn check(&self, message: &AuthMessage) -> FzResult<()> {
+        let now_ms = crate::unix_now().as_millis() as u64;
+        self.check_at(message, now_ms)
+    }
+
+    /// 以指定时间（Unix 毫秒）检查认证消息
+    pub fn check_at(&self, message: &AuthMessage, now_ms: u64) -> FzResult<()> {
+        let window_ms = self.window.as_millis() as u64;
+        if message.timestamp.abs_diff(now_ms) > window_ms {
+            return Err(FzStreamError::auth(
+                AuthErrorCode::StaleTimestamp,
+                format!("auth timestamp {} is outside the {} ms window", message.timestamp, window_ms),
+            ));
+        }
+
+        let nonce = match message.nonce.as_deref() {
+            Some(nonce) if !nonce.is_empty() => nonce,
+            _ if self.require_nonce => {
+                return Err(FzStreamError::auth(AuthErrorCode::MissingNonce, "auth message has no nonce"));
+            }
+            _ => return Ok(()),
+        };
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        Self::evict_expired(&mut cache, now_ms);
+
+        let key = (message.client_id.clone(), nonce.to_string());
+        if cache.seen.contains_key(&key) {
+            return Err(FzStreamError::auth(AuthErrorCode::ReplayDetected, "auth nonce has already been used"));
+        }
+        if cache.seen.len() >= self.max_entries {
+            return Err(FzStreamError::auth(
+                AuthErrorCode::ReplayCacheFull,
+                format!("too many auth attempts: {} nonces are still within the window", cache.seen.len()),
+            ));
+        }
+
+        // 时间戳在窗口内的消息最晚在 timestamp + window 之后失效，在此之前都需要记住 nonce
+        let expires_at = message.timestamp.saturating_add(window_ms).max(now_ms);
+        cache.seen.insert(key.clone(), expires_at);
+        cache.order.push_back((key, expires_at));
+        Ok(())
+    }
+
+    /// 当前缓存的 nonce 数量
+    pub fn cached_nonces(&self) -> usize {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).seen.len()
+    }
+
+    fn evict_expired(cache: &mut NonceCache, now_ms: u64) {
+        while let Some((_, expires_at)) = cache.order.front() {
+            if *expires_at > now_ms {
+                break;
+            }
+            if let Some((key, expires_at)) = cache.order.pop_front() {
+                Self::forget(cache, &key, expires_at);
+            }
+        }
+    }
+
+    /// 只删除与队列记录对应的条目，避免误删之后重新写入的同名 nonce
+    fn forget(cache: &mut NonceCache, key: &(String, String), expires_at: u64) {
+        if cache.seen.get(key) == Some(&expires_at) {
+            cache.seen.remove(key);
+        }
+    }
+}
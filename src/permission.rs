@@ -0,0 +1,173 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+use crate::events::{EventType, EventTypeFilter, ALL_EVENT_TYPES};
+
+/// 令牌权限
+///
+/// 与字符串互相转换，令牌中仍以字符串保存：
+/// `read`、`write`、`admin`、`stream`（所有事件）、`stream:<模式>`。
+/// 模式以 `*` 结尾时按事件类型名前缀匹配（如 `stream:PumpFun*`），否则精确匹配。
+/// 无法识别的字符串保留为 `Custom`。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Permission {
+    Read,
+    Write,
+    /// 管理权限，包含所有其他权限
+    Admin,
+    /// 订阅匹配模式的事件，`*` 表示所有事件
+    Stream(String),
+    Custom(String),
+}
+
+impl Permission {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "read" => Permission::Read,
+            "write" => Permission::Write,
+            "admin" => Permission::Admin,
+            "stream" => Permission::Stream("*".to_string()),
+            _ => match value.strip_prefix("stream:") {
+                Some(pattern) if !pattern.is_empty() => Permission::Stream(pattern.to_string()),
+                _ => Permission::Custom(value.to_string()),
+            },
+        }
+    }
+
+    /// 是否允许订阅该事件类型
+    pub fn allows_event(&self, event_type: &EventType) -> bool {
+        match self {
+            Permission::Admin => true,
+            Permission::Stream(pattern) => match pattern.strip_suffix('*') {
+                Some(prefix) => event_type.to_string().starts_with(prefix),
+                None => event_type.to_string() == *pattern,
+            },
+            _ => false,
+        }
+    }
+
+    /// 是否允许订阅所有事件
+    pub fn allows_all_events(&self) -> bool {
+        match self {
+            Permission::Admin => true,
+            Permission::Stream(pattern) => pattern == "*",
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Read => write!(f, "read"),
+            Permission::Write => write!(f, "write"),
+            Permission::Admin => write!(f, "admin"),
+            Permission::Stream(pattern) if pattern == "*" => write!(f, "stream"),
+            Permission::Stream(pattern) => write!(f, "stream:{}", pattern),
+            Permission::Custom(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for Permission {
+    fn from(value: &str) -> Self {
+        Permission::parse(value)
+    }
+}
+
+impl From<String> for Permission {
+    fn from(value: String) -> Self {
+        Permission::parse(&value)
+    }
+}
+
+impl From<Permission> for String {
+    fn from(permission: Permission) -> Self {
+        permission.to_string()
+    }
+}
+
+/// 一个令牌的全部权限
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionSet {
+    permissions: Vec<Permission>,
+}
+
+impl PermissionSet {
+    pub fn new(permissions: Vec<Permission>) -> Self {
+        Self { permissions }
+    }
+
+    /// 从令牌中的权限字符串解析
+    pub fn from_strings<S: AsRef<str>>(permissions: &[S]) -> Self {
+        Self::new(permissions.iter().map(|p| Permission::parse(p.as_ref())).collect())
+    }
+
+    pub fn permissions(&self) -> &[Permission] {
+        &self.permissions
+    }
+
+    /// 是否拥有该权限（`admin` 拥有所有权限）
+    pub fn contains(&self, permission: &Permission) -> bool {
+        self.permissions.contains(&Permission::Admin) || self.permissions.contains(permission)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.permissions.contains(&Permission::Admin)
+    }
+
+    /// 是否拥有任意订阅权限
+    pub fn can_stream(&self) -> bool {
+        self.permissions
+            .iter()
+            .any(|p| matches!(p, Permission::Admin | Permission::Stream(_)))
+    }
+
+    pub fn allows_event(&self, event_type: &EventType) -> bool {
+        self.permissions.iter().any(|p| p.allows_event(event_type))
+    }
+
+    /// 权限允许订阅的事件过滤器
+    pub fn event_filter(&self) -> EventTypeFilter {
+        if self.permissions.iter().any(Permission::allows_all_events) {
+            return EventTypeFilter::allow_all();
+        }
+        EventTypeFilter::allow_only(
+            ALL_EVENT_TYPES
+                .iter()
+                .filter(|event_type| self.allows_event(event_type))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// 将客户端请求的过滤器限制在权限范围内
+    ///
+    /// 未请求过滤器时返回权限允许的全部事件；没有订阅权限或交集为空时返回 `PermissionDenied`。
+    pub fn authorize_filter(&self, requested: Option<&EventTypeFilter>) -> FzResult<EventTypeFilter> {
+        if !self.can_stream() {
+            return Err(FzStreamError::auth(AuthErrorCode::PermissionDenied, "token has no stream permission"));
+        }
+
+        let allowed = self.event_filter();
+        let filter = match requested {
+            Some(requested) => allowed.intersect(requested),
+            None => allowed,
+        };
+        if !filter.allow_all && filter.allowed_types.is_empty() {
+            return Err(FzStreamError::auth(
+                AuthErrorCode::PermissionDenied,
+                "requested events are not covered by token permissions",
+            ));
+        }
+        Ok(filter)
+    }
+}
+
+impl<S: AsRef<str>> From<&[S]> for PermissionSet {
+    fn from(permissions: &[S]) -> Self {
+        Self::from_strings(permissions)
+    }
+}
@@ -0,0 +1,226 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::auth::TokenClaims;
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+
+/// 客户端配额，字段为 None 表示不限制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientQuota {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_events_per_sec: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl ClientQuota {
+    /// 不限制的配额
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_connections.is_none() && self.max_events_per_sec.is_none() && self.max_bytes_per_sec.is_none()
+    }
+}
+
+/// 令牌桶
+///
+/// 以 `rate` 每秒的速度补充，最多积累 `capacity` 个令牌。
+/// 单次请求超过容量时，桶满即可放行并记为欠额，避免大事件永远无法通过。
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// 创建满桶
+    pub fn new(rate: f64, capacity: f64) -> Self {
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// 每秒速率，突发容量为 1 秒的量
+    pub fn per_second(rate: u64) -> Self {
+        Self::new(rate as f64, rate as f64)
+    }
+
+    pub fn try_take(&mut self, amount: f64) -> bool {
+        self.try_take_at(amount, Instant::now())
+    }
+
+    pub fn try_take_at(&mut self, amount: f64, now: Instant) -> bool {
+        if !self.can_take_at(amount, now) {
+            return false;
+        }
+        self.tokens -= amount;
+        true
+    }
+
+    /// 补充后是否有足够的令牌（不消耗）
+    pub fn can_take_at(&mut self, amount: f64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+        self.tokens >= amount.min(self.capacity)
+    }
+
+    /// 当前可用令牌数
+    pub fn available(&self) -> f64 {
+        self.tokens
+    }
+}
+
+#[derive(Debug)]
+struct ClientLimits {
+    quota: ClientQuota,
+    connections: u32,
+    events: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+impl ClientLimits {
+    fn new(quota: ClientQuota) -> Self {
+        Self {
+            quota,
+            connections: 0,
+            events: quota.max_events_per_sec.map(|rate| TokenBucket::per_second(rate as u64)),
+            bytes: quota.max_bytes_per_sec.map(TokenBucket::per_second),
+        }
+    }
+}
+
+/// 按认证身份限流
+///
+/// 服务器在认证成功后调用 `set_quota_from_claims`（或以验证通过的主体调用 `set_quota`），之后每个连接调用
+/// `acquire_connection`/`release_connection`，每个事件发送前调用 `try_send`。
+/// 限流按令牌中的身份记录，不能使用客户端自报的 `client_id`，否则换一个编号即可绕过配额。
+/// 未设置配额的身份不受限制。
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    clients: Mutex<HashMap<String, ClientLimits>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置身份的配额
+    ///
+    /// 配额未变化时保留现有速率桶（重新连接不会补满额度），变化时保留当前连接数、速率桶重新开始。
+    pub fn set_quota(&self, identity: &str, quota: ClientQuota) {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        let connections = match clients.get(identity) {
+            Some(limits) if limits.quota == quota => return,
+            Some(limits) => limits.connections,
+            None => 0,
+        };
+        let mut limits = ClientLimits::new(quota);
+        limits.connections = connections;
+        clients.insert(identity.to_string(), limits);
+    }
+
+    /// 按令牌声明设置配额，以 `user_id` 为身份（令牌未携带配额时不限制），返回使用的身份
+    pub fn set_quota_from_claims<'a>(&self, claims: &'a TokenClaims) -> &'a str {
+        self.set_quota(&claims.user_id, claims.quota.unwrap_or_default());
+        &claims.user_id
+    }
+
+    pub fn quota(&self, identity: &str) -> Option<ClientQuota> {
+        let clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.get(identity).map(|limits| limits.quota)
+    }
+
+    /// 占用一个连接名额，超过 `max_connections` 时返回错误
+    pub fn acquire_connection(&self, identity: &str) -> FzResult<()> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        let limits = match clients.get_mut(identity) {
+            Some(limits) => limits,
+            None => return Ok(()),
+        };
+
+        if limits.quota.max_connections.is_some_and(|max| limits.connections >= max) {
+            return Err(FzStreamError::auth(
+                AuthErrorCode::ConnectionLimitExceeded,
+                format!("{} already has {} connections", identity, limits.connections),
+            ));
+        }
+        limits.connections += 1;
+        Ok(())
+    }
+
+    /// 释放一个连接名额
+    pub fn release_connection(&self, identity: &str) {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(limits) = clients.get_mut(identity) {
+            limits.connections = limits.connections.saturating_sub(1);
+        }
+    }
+
+    /// 当前连接数
+    pub fn connections(&self, identity: &str) -> u32 {
+        let clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.get(identity).map_or(0, |limits| limits.connections)
+    }
+
+    /// 是否允许向该身份发送一个 `bytes` 字节的事件，允许时消耗相应额度
+    pub fn try_send(&self, identity: &str, bytes: usize) -> bool {
+        self.try_send_at(identity, bytes, Instant::now())
+    }
+
+    pub fn try_send_at(&self, identity: &str, bytes: usize, now: Instant) -> bool {
+        self.check_send_at(identity, bytes, now).is_ok()
+    }
+
+    /// 与 `try_send` 相同，超限时返回 `RateLimited` 错误并指明超出的配额
+    pub fn check_send(&self, identity: &str, bytes: usize) -> FzResult<()> {
+        self.check_send_at(identity, bytes, Instant::now())
+    }
+
+    pub fn check_send_at(&self, identity: &str, bytes: usize, now: Instant) -> FzResult<()> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        let limits = match clients.get_mut(identity) {
+            Some(limits) => limits,
+            None => return Ok(()),
+        };
+
+        // 两个桶都有余量时才同时扣减，避免只扣掉其中一个
+        let bytes = bytes as f64;
+        let mut exceeded = Vec::new();
+        if limits.events.as_mut().is_some_and(|bucket| !bucket.can_take_at(1.0, now)) {
+            exceeded.push(format!("{} events/s", limits.quota.max_events_per_sec.unwrap_or_default()));
+        }
+        if limits.bytes.as_mut().is_some_and(|bucket| !bucket.can_take_at(bytes, now)) {
+            exceeded.push(format!("{} bytes/s", limits.quota.max_bytes_per_sec.unwrap_or_default()));
+        }
+        if !exceeded.is_empty() {
+            return Err(FzStreamError::auth(
+                AuthErrorCode::RateLimited,
+                format!("{} exceeded its rate quota of {}", identity, exceeded.join(" and ")),
+            ));
+        }
+
+        let events_taken = limits.events.as_mut().is_none_or(|bucket| bucket.try_take_at(1.0, now));
+        let bytes_taken = limits.bytes.as_mut().is_none_or(|bucket| bucket.try_take_at(bytes, now));
+        debug_assert!(events_taken && bytes_taken);
+        Ok(())
+    }
+
+    /// 移除该身份的配额和计数
+    pub fn remove(&self, identity: &str) {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.remove(identity);
+    }
+}
@@ -0,0 +1,185 @@
+use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::events::EventMessage;
+
+/// 默认重放缓冲区最多保留的事件数
+pub const DEFAULT_REPLAY_MAX_EVENTS: usize = 100_000;
+/// 默认重放缓冲区最多保留的时长
+pub const DEFAULT_REPLAY_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// 断线重连后客户端发送的续传请求
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResumeRequest {
+    /// 连续收到的最后一个序列号（0 表示没有，此时按 slot 续传）
+    pub last_sequence: u64,
+    /// 最后处理完成的 slot（0 表示没有）
+    pub last_slot: u64,
+}
+
+/// 服务器对续传请求的响应
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ResumeResponse {
+    /// 可以续传，随后重放 `replay_count` 个事件，第一个序列号为 `from_sequence`
+    Resumed {
+        from_sequence: u64,
+        replay_count: u64,
+    },
+    /// 缺失的事件已不在缓冲区中，客户端需要重新同步
+    ResyncRequired {
+        oldest_available: Option<u64>,
+        latest_sequence: u64,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct ReplayEntry {
+    slot: u64,
+    inserted_at: Instant,
+    message: EventMessage,
+}
+
+/// 服务器端重放缓冲区
+///
+/// 按事件数量和时长两个维度限制保留的事件，用于响应客户端的续传请求。
+/// 只保存带序列号的事件，且序列号必须单调递增。
+#[derive(Debug, Clone)]
+pub struct ReplayBuffer {
+    entries: VecDeque<ReplayEntry>,
+    max_events: usize,
+    max_age: Duration,
+}
+
+impl Default for ReplayBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_MAX_EVENTS, DEFAULT_REPLAY_MAX_AGE)
+    }
+}
+
+impl ReplayBuffer {
+    pub fn new(max_events: usize, max_age: Duration) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            max_events,
+            max_age,
+        }
+    }
+
+    /// 保存已发送的事件，返回是否被保存（未分配或序列号倒退的事件会被忽略）
+    pub fn push(&mut self, slot: u64, message: EventMessage) -> bool {
+        self.push_at(slot, message, Instant::now())
+    }
+
+    fn push_at(&mut self, slot: u64, message: EventMessage, now: Instant) -> bool {
+        if message.sequence == 0 || Some(message.sequence) <= self.latest_sequence() {
+            return false;
+        }
+
+        self.entries.push_back(ReplayEntry {
+            slot,
+            inserted_at: now,
+            message,
+        });
+        while self.entries.len() > self.max_events {
+            self.entries.pop_front();
+        }
+        self.evict_expired_at(now);
+        true
+    }
+
+    /// 清理超过保留时长的事件
+    pub fn evict_expired(&mut self) {
+        self.evict_expired_at(Instant::now());
+    }
+
+    fn evict_expired_at(&mut self, now: Instant) {
+        while let Some(entry) = self.entries.front() {
+            if now.duration_since(entry.inserted_at) > self.max_age {
+                self.entries.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 缓冲区中最早的序列号
+    pub fn oldest_sequence(&self) -> Option<u64> {
+        self.entries.front().map(|entry| entry.message.sequence)
+    }
+
+    /// 缓冲区中最新的序列号
+    pub fn latest_sequence(&self) -> Option<u64> {
+        self.entries.back().map(|entry| entry.message.sequence)
+    }
+
+    /// 处理续传请求，返回响应及需要重放的事件
+    pub fn resume(&self, request: &ResumeRequest) -> (ResumeResponse, Vec<EventMessage>) {
+        let now = Instant::now();
+        let live: Vec<&ReplayEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| now.duration_since(entry.inserted_at) <= self.max_age)
+            .collect();
+
+        let oldest_available = live.first().map(|entry| entry.message.sequence);
+        let latest_sequence = live.last().map(|entry| entry.message.sequence).unwrap_or(0);
+        let resync = |reason: &str| {
+            (
+                ResumeResponse::ResyncRequired {
+                    oldest_available,
+                    latest_sequence,
+                    reason: reason.to_string(),
+                },
+                Vec::new(),
+            )
+        };
+
+        let replay: Vec<EventMessage> = if request.last_sequence > 0 {
+            if request.last_sequence > latest_sequence {
+                return resync("last_sequence is ahead of the server stream");
+            }
+            if request.last_sequence < latest_sequence
+                && oldest_available.is_none_or(|oldest| request.last_sequence + 1 < oldest)
+            {
+                return resync("events after last_sequence are no longer buffered");
+            }
+            live.iter()
+                .filter(|entry| entry.message.sequence > request.last_sequence)
+                .map(|entry| entry.message.clone())
+                .collect()
+        } else if request.last_slot > 0 {
+            // 只有缓冲区仍包含 last_slot 及之前的事件，才能保证之后的 slot 完整
+            if live.first().is_none_or(|entry| entry.slot > request.last_slot) {
+                return resync("events after last_slot are no longer buffered");
+            }
+            live.iter()
+                .filter(|entry| entry.slot > request.last_slot)
+                .map(|entry| entry.message.clone())
+                .collect()
+        } else {
+            return resync("resume request carries neither sequence nor slot");
+        };
+
+        let from_sequence = replay
+            .first()
+            .map(|message| message.sequence)
+            .unwrap_or(latest_sequence + 1);
+        (
+            ResumeResponse::Resumed {
+                from_sequence,
+                replay_count: replay.len() as u64,
+            },
+            replay,
+        )
+    }
+}
@@ -0,0 +1,162 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+
+/// 吊销条目，按令牌编号（JWT `jti`、会话编号）、用户编号、JWT 签名密钥编号（`kid`）和 API 密钥编号吊销
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevokedIds {
+    #[serde(default)]
+    pub token_ids: BTreeSet<String>,
+    #[serde(default)]
+    pub user_ids: BTreeSet<String>,
+    /// JWT 签名密钥编号
+    #[serde(default)]
+    pub key_ids: BTreeSet<String>,
+    /// API 密钥编号（与 JWT `kid` 分开，避免同名编号互相吊销）
+    #[serde(default)]
+    pub api_key_ids: BTreeSet<String>,
+}
+
+impl RevokedIds {
+    pub fn is_empty(&self) -> bool {
+        self.token_ids.is_empty() && self.user_ids.is_empty() && self.key_ids.is_empty() && self.api_key_ids.is_empty()
+    }
+}
+
+/// 令牌吊销列表
+///
+/// 验证器通过 `Arc` 共享同一个列表，`reload` 原地替换内容，无需重建验证器。
+/// 文件格式为 `RevokedIds` 的 JSON。
+#[derive(Debug, Default)]
+pub struct RevocationList {
+    path: Option<PathBuf>,
+    revoked: RwLock<RevokedIds>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl RevocationList {
+    /// 创建仅在内存中的吊销列表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载吊销列表，文件不存在时返回错误
+    pub fn open(path: impl AsRef<Path>) -> FzResult<Self> {
+        let list = Self {
+            path: Some(path.as_ref().to_path_buf()),
+            ..Self::default()
+        };
+        list.reload()?;
+        Ok(list)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 重新读取文件；读取或解析失败时保留原有内容
+    pub fn reload(&self) -> FzResult<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        let content = std::fs::read(path).map_err(|e| {
+            FzStreamError::Config(format!("failed to read revocation list {}: {}", path.display(), e))
+        })?;
+        let revoked: RevokedIds = serde_json::from_slice(&content).map_err(|e| {
+            FzStreamError::Config(format!("failed to parse revocation list {}: {}", path.display(), e))
+        })?;
+
+        *self.revoked.write().unwrap_or_else(|e| e.into_inner()) = revoked;
+        *self.modified.lock().unwrap_or_else(|e| e.into_inner()) = modified;
+        Ok(())
+    }
+
+    /// 文件修改时间变化时重新读取，返回是否重新读取（供服务器定时调用）
+    pub fn reload_if_modified(&self) -> FzResult<bool> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(false),
+        };
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).map_err(|e| {
+            FzStreamError::Config(format!("failed to stat revocation list {}: {}", path.display(), e))
+        })?;
+        if *self.modified.lock().unwrap_or_else(|e| e.into_inner()) == Some(modified) {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// 用给定内容替换整个列表（不写文件）
+    pub fn replace(&self, revoked: RevokedIds) {
+        *self.revoked.write().unwrap_or_else(|e| e.into_inner()) = revoked;
+    }
+
+    /// 当前内容的副本
+    pub fn snapshot(&self) -> RevokedIds {
+        self.revoked.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn revoke_token(&self, token_id: impl Into<String>) {
+        self.revoked.write().unwrap_or_else(|e| e.into_inner()).token_ids.insert(token_id.into());
+    }
+
+    pub fn revoke_user(&self, user_id: impl Into<String>) {
+        self.revoked.write().unwrap_or_else(|e| e.into_inner()).user_ids.insert(user_id.into());
+    }
+
+    /// 吊销 JWT 签名密钥，该密钥签发的所有令牌失效
+    pub fn revoke_key(&self, key_id: impl Into<String>) {
+        self.revoked.write().unwrap_or_else(|e| e.into_inner()).key_ids.insert(key_id.into());
+    }
+
+    pub fn revoke_api_key(&self, key_id: impl Into<String>) {
+        self.revoked.write().unwrap_or_else(|e| e.into_inner()).api_key_ids.insert(key_id.into());
+    }
+
+    /// 将当前内容写回文件（先写临时文件再重命名）
+    pub fn save(&self) -> FzResult<()> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| FzStreamError::Config("revocation list has no file".to_string()))?;
+        let content = serde_json::to_vec_pretty(&self.snapshot())?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, content)
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .map_err(|e| FzStreamError::Config(format!("failed to write revocation list {}: {}", path.display(), e)))?;
+        *self.modified.lock().unwrap_or_else(|e| e.into_inner()) =
+            std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        Ok(())
+    }
+
+    /// 检查令牌是否被吊销，任一编号命中时返回 `TokenRevoked`（`key_id` 为 JWT 签名密钥编号）
+    pub fn check(&self, token_id: Option<&str>, user_id: Option<&str>, key_id: Option<&str>) -> FzResult<()> {
+        let revoked = self.revoked.read().unwrap_or_else(|e| e.into_inner());
+        let reason = if token_id.is_some_and(|id| revoked.token_ids.contains(id)) {
+            "token has been revoked"
+        } else if user_id.is_some_and(|id| revoked.user_ids.contains(id)) {
+            "user has been revoked"
+        } else if key_id.is_some_and(|id| revoked.key_ids.contains(id)) {
+            "signing key has been revoked"
+        } else {
+            return Ok(());
+        };
+        Err(FzStreamError::auth(AuthErrorCode::TokenRevoked, reason))
+    }
+
+    /// 检查 API 密钥是否被吊销
+    pub fn check_api_key(&self, key_id: &str) -> FzResult<()> {
+        let revoked = self.revoked.read().unwrap_or_else(|e| e.into_inner());
+        if revoked.api_key_ids.contains(key_id) {
+            return Err(FzStreamError::auth(AuthErrorCode::TokenRevoked, "API key has been revoked"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 默认最多跟踪的缺失序列号数量
+pub const DEFAULT_MAX_TRACKED_MISSING: usize = 4096;
+/// 默认乱序窗口：缺失序列号落后最高序列号超过此值即视为丢失
+pub const DEFAULT_REORDER_WINDOW: u64 = 256;
+
+/// 服务器端按流分配单调递增的序列号（从 1 开始，0 表示未分配）
+#[derive(Debug)]
+pub struct SequenceGenerator {
+    first: u64,
+    next: AtomicU64,
+}
+
+impl Default for SequenceGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceGenerator {
+    pub fn new() -> Self {
+        Self::starting_at(1)
+    }
+
+    /// 从指定序列号开始分配（例如服务器重启后接续）
+    pub fn starting_at(first: u64) -> Self {
+        let first = first.max(1);
+        Self {
+            first,
+            next: AtomicU64::new(first),
+        }
+    }
+
+    /// 分配下一个序列号
+    pub fn next_sequence(&self) -> u64 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// 连续分配 `count` 个序列号，返回第一个（用于批量消息）
+    pub fn next_range(&self, count: u64) -> u64 {
+        self.next.fetch_add(count, Ordering::Relaxed)
+    }
+
+    /// 最近一次分配的序列号（尚未分配时为 0）
+    pub fn last_assigned(&self) -> u64 {
+        match self.next.load(Ordering::Relaxed) {
+            next if next == self.first => 0,
+            next => next - 1,
+        }
+    }
+}
+
+/// 单个序列号的检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SequenceStatus {
+    /// 按顺序到达
+    InOrder,
+    /// 出现缺口，`expected..received` 之间的序列号缺失
+    Gap { expected: u64, received: u64 },
+    /// 重复到达
+    Duplicate(u64),
+    /// 迟到的序列号，填补了之前的缺口
+    Reordered(u64),
+    /// 消息未携带序列号
+    Unsequenced,
+}
+
+/// 序列号统计
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SequenceStats {
+    pub received: u64,
+    pub gaps: u64,
+    pub missing_total: u64,
+    pub duplicates: u64,
+    pub reordered: u64,
+}
+
+/// 客户端序列号跟踪器
+///
+/// 检测缺口、重复和乱序，并判断是否需要向服务器请求重新同步。
+#[derive(Debug, Clone)]
+pub struct SequenceTracker {
+    highest: Option<u64>,
+    missing: BTreeSet<u64>,
+    max_tracked_missing: usize,
+    reorder_window: u64,
+    /// 缺口过大无法逐一跟踪时，第一个未跟踪的缺失序列号（重新同步前保持不变）
+    first_missing: Option<u64>,
+    stats: SequenceStats,
+}
+
+impl Default for SequenceTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::with_limits(DEFAULT_MAX_TRACKED_MISSING, DEFAULT_REORDER_WINDOW)
+    }
+
+    /// 指定最多跟踪的缺失序列号数量和乱序窗口
+    pub fn with_limits(max_tracked_missing: usize, reorder_window: u64) -> Self {
+        Self {
+            highest: None,
+            missing: BTreeSet::new(),
+            max_tracked_missing,
+            reorder_window,
+            first_missing: None,
+            stats: SequenceStats::default(),
+        }
+    }
+
+    /// 记录收到的序列号
+    pub fn observe(&mut self, sequence: u64) -> SequenceStatus {
+        if sequence == 0 {
+            return SequenceStatus::Unsequenced;
+        }
+        self.stats.received += 1;
+
+        let highest = match self.highest {
+            None => {
+                self.highest = Some(sequence);
+                return SequenceStatus::InOrder;
+            }
+            Some(highest) => highest,
+        };
+
+        if sequence == highest + 1 {
+            self.highest = Some(sequence);
+            SequenceStatus::InOrder
+        } else if sequence > highest {
+            let expected = highest + 1;
+            let gap = sequence - expected;
+            self.stats.gaps += 1;
+            self.stats.missing_total += gap;
+
+            if self.missing.len() as u64 + gap > self.max_tracked_missing as u64 {
+                self.first_missing.get_or_insert(expected);
+            } else {
+                self.missing.extend(expected..sequence);
+            }
+            self.highest = Some(sequence);
+            SequenceStatus::Gap { expected, received: sequence }
+        } else if self.missing.remove(&sequence) {
+            self.stats.reordered += 1;
+            SequenceStatus::Reordered(sequence)
+        } else {
+            self.stats.duplicates += 1;
+            SequenceStatus::Duplicate(sequence)
+        }
+    }
+
+    /// 收到的最高序列号
+    pub fn highest(&self) -> Option<u64> {
+        self.highest
+    }
+
+    /// 连续收到的最后一个序列号（其之前的序列号均已收到）
+    pub fn last_contiguous(&self) -> Option<u64> {
+        let tracked = self.missing.iter().next().copied();
+        let first_missing = match (tracked, self.first_missing) {
+            (Some(tracked), Some(untracked)) => Some(tracked.min(untracked)),
+            (tracked, untracked) => tracked.or(untracked),
+        };
+        match first_missing {
+            Some(first_missing) => Some(first_missing - 1),
+            None => self.highest,
+        }
+    }
+
+    /// 当前缺失的序列号
+    pub fn missing(&self) -> Vec<u64> {
+        self.missing.iter().copied().collect()
+    }
+
+    /// 是否需要请求重新同步
+    ///
+    /// 缺失序列号超出乱序窗口（不太可能再到达）或缺口过大无法逐一跟踪时返回 true。
+    pub fn needs_resync(&self) -> bool {
+        if self.first_missing.is_some() {
+            return true;
+        }
+        match (self.missing.iter().next(), self.highest) {
+            (Some(&oldest), Some(highest)) => highest - oldest > self.reorder_window,
+            _ => false,
+        }
+    }
+
+    pub fn stats(&self) -> SequenceStats {
+        self.stats
+    }
+
+    /// 重新同步后从 `sequence` 开始跟踪（下一个期望的序列号为 `sequence + 1`）
+    pub fn reset(&mut self, sequence: u64) {
+        self.highest = if sequence == 0 { None } else { Some(sequence) };
+        self.missing.clear();
+        self.first_missing = None;
+    }
+}
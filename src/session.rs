@@ -0,0 +1,180 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::credentials::to_hex;
+use crate::error::{AuthErrorCode, FzResult, FzStreamError};
+use crate::ServerConfig;
+
+/// 会话令牌前缀
+pub const SESSION_TOKEN_PREFIX: &str = "sess_";
+/// 默认会话有效期（1 小时）
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(3600);
+/// 允许的时钟偏差（秒）
+const SESSION_CLOCK_SKEW_SECS: u64 = 60;
+
+/// 会话令牌中的声明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionClaims {
+    /// 会话编号，刷新后保持不变
+    pub session_id: String,
+    pub user_id: String,
+    pub permissions: Vec<String>,
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+/// 从 `auth_secret_key` 派生会话密钥时使用的上下文
+const SESSION_KEY_CONTEXT: &[u8] = b"fzstream-session-v1";
+
+/// 会话管理
+///
+/// 令牌格式为 `sess_<base64url(JSON 声明)>.<base64url(HMAC-SHA256)>`，
+/// 内容被篡改或使用其他密钥签名时验证失败。吊销按会话编号记录，同一会话刷新出的令牌一并失效。
+pub struct SessionManager {
+    key: hmac::Key,
+    ttl: Duration,
+    rng: SystemRandom,
+    revoked: RwLock<HashMap<String, u64>>, // 会话编号 -> 记录可清理的时间
+}
+
+impl std::fmt::Debug for SessionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionManager")
+            .field("ttl", &self.ttl)
+            .field("revoked", &self.revoked_count())
+            .finish()
+    }
+}
+
+impl SessionManager {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            ttl,
+            rng: SystemRandom::new(),
+            revoked: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 根据服务器配置创建（未配置 `auth_secret_key` 时返回 None）
+    ///
+    /// 会话密钥为 `HMAC-SHA256(auth_secret_key, "fzstream-session-v1")`，与 HS256 JWT 密钥互不相同。
+    pub fn from_server_config(config: &ServerConfig) -> Option<Self> {
+        config.auth_secret_key.as_ref().map(|secret| {
+            let secret_key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+            let session_key = hmac::sign(&secret_key, SESSION_KEY_CONTEXT);
+            Self::new(session_key.as_ref(), Duration::from_secs(config.session_ttl_secs))
+        })
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 创建新会话
+    pub fn issue(&self, user_id: impl Into<String>, permissions: Vec<String>) -> FzResult<String> {
+        let mut session_id = [0u8; 16];
+        self.rng
+            .fill(&mut session_id)
+            .map_err(|_| FzStreamError::Config("system random generator failed".to_string()))?;
+
+        let issued_at = crate::unix_now().as_secs();
+        self.sign(&SessionClaims {
+            session_id: to_hex(&session_id),
+            user_id: user_id.into(),
+            permissions,
+            issued_at,
+            expires_at: issued_at.saturating_add(self.ttl.as_secs()),
+        })
+    }
+
+    /// 验证令牌并返回声明
+    pub fn verify(&self, token: &str) -> FzResult<SessionClaims> {
+        self.verify_at(token, crate::unix_now().as_secs())
+    }
+
+    /// 以指定时间（Unix 秒）验证令牌
+    pub fn verify_at(&self, token: &str, now: u64) -> FzResult<SessionClaims> {
+        let claims = self.decode(token)?;
+
+        if now >= claims.expires_at {
+            return Err(FzStreamError::auth(AuthErrorCode::ExpiredToken, "session token expired"));
+        }
+        if claims.issued_at > now.saturating_add(SESSION_CLOCK_SKEW_SECS) {
+            return Err(FzStreamError::auth(AuthErrorCode::TokenNotYetValid, "session token issued in the future"));
+        }
+        if self.is_revoked(&claims.session_id) {
+            return Err(FzStreamError::auth(AuthErrorCode::TokenRevoked, "session has been revoked"));
+        }
+        Ok(claims)
+    }
+
+    /// 刷新会话：旧令牌仍有效时签发同一会话的新令牌，有效期重新计算
+    pub fn refresh(&self, token: &str) -> FzResult<String> {
+        let mut claims = self.verify(token)?;
+        claims.issued_at = crate::unix_now().as_secs();
+        claims.expires_at = claims.issued_at.saturating_add(self.ttl.as_secs());
+        self.sign(&claims)
+    }
+
+    /// 吊销令牌所属的会话
+    pub fn revoke(&self, token: &str) -> FzResult<()> {
+        let claims = self.decode(token)?;
+        self.revoke_session(&claims.session_id);
+        Ok(())
+    }
+
+    /// 按会话编号吊销
+    pub fn revoke_session(&self, session_id: &str) {
+        // 吊销后无法再刷新，该会话的令牌最晚在一个 TTL 后全部过期
+        let forget_at = crate::unix_now().as_secs().saturating_add(self.ttl.as_secs());
+        let mut revoked = self.revoked.write().unwrap_or_else(|e| e.into_inner());
+        revoked.insert(session_id.to_string(), forget_at);
+    }
+
+    pub fn is_revoked(&self, session_id: &str) -> bool {
+        let revoked = self.revoked.read().unwrap_or_else(|e| e.into_inner());
+        revoked.contains_key(session_id)
+    }
+
+    /// 清理已不可能再被使用的吊销记录
+    pub fn purge_revoked(&self) {
+        let now = crate::unix_now().as_secs();
+        let mut revoked = self.revoked.write().unwrap_or_else(|e| e.into_inner());
+        revoked.retain(|_, forget_at| *forget_at > now);
+    }
+
+    pub fn revoked_count(&self) -> usize {
+        self.revoked.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn sign(&self, claims: &SessionClaims) -> FzResult<String> {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?);
+        let signature = hmac::sign(&self.key, payload.as_bytes());
+        Ok(format!("{}{}.{}", SESSION_TOKEN_PREFIX, payload, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    /// 校验签名并解析声明（不检查有效期和吊销）
+    fn decode(&self, token: &str) -> FzResult<SessionClaims> {
+        let malformed = || FzStreamError::auth(AuthErrorCode::MalformedToken, "invalid session token format");
+
+        let (payload, signature) = token
+            .strip_prefix(SESSION_TOKEN_PREFIX)
+            .and_then(|rest| rest.split_once('.'))
+            .ok_or_else(malformed)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| malformed())?;
+        hmac::verify(&self.key, payload.as_bytes(), &signature)
+            .map_err(|_| FzStreamError::auth(AuthErrorCode::InvalidToken, "invalid session token signature"))?;
+
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| malformed())?;
+        serde_json::from_slice(&payload).map_err(|_| malformed())
+    }
+}
+
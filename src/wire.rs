@@ -0,0 +1,571 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+use crate::batch::EventBatch;
+use crate::codec::CodecRegistry;
+use crate::control::ServerMessage;
+use crate::events::EventMessage;
+use crate::{CompressionType, SerializationProtocol};
+
+/// 帧魔数
+pub const FRAME_MAGIC: [u8; 4] = *b"FZSF";
+/// 协议主版本号（主版本不一致时拒绝解码）
+pub const WIRE_VERSION_MAJOR: u8 = 1;
+/// 协议次版本号（次版本不一致时仍可互通）
+///
+/// 次版本只能在消息末尾追加字段：旧版本解码时忽略多出的字段，新版本按帧头中的次版本解码旧布局。
+/// 1：`EventMessage` 追加 `dictionary_id`、`sequence`，`EventBatch` 追加 `first_sequence`
+pub const WIRE_VERSION_MINOR: u8 = 1;
+/// 当前版本的帧头长度（字节）
+pub const FRAME_HEADER_LEN: usize = 18;
+
+/// 帧标志：事件数据已压缩
+pub const FLAG_COMPRESSED: u8 = 0x01;
+/// 帧标志：事件数据使用 zstd 字典压缩
+pub const FLAG_DICTIONARY: u8 = 0x02;
+/// 帧标志的高 4 位保存消息类型（`FrameKind`）
+const FRAME_KIND_SHIFT: u8 = 4;
+
+/// 流式解码器默认允许的最大帧长度（16 MiB）
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// 帧编解码错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// 魔数不匹配
+    BadMagic([u8; 4]),
+    /// 不支持的主版本号
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// 帧头长度字段非法
+    InvalidHeaderLength(u8),
+    /// 数据不足以构成完整的帧
+    Truncated { needed: usize, available: usize },
+    /// 帧超过允许的最大长度
+    Oversized { size: usize, max: usize },
+    /// 负载校验和不匹配
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// 未知的序列化编号
+    UnknownSerialization(u8),
+    /// 未知的编解码器编号
+    UnknownCodec(u8),
+    /// 负载序列化/反序列化失败
+    Serialization(String),
+    /// 帧中的消息类型与期望不符
+    UnexpectedKind { expected: u8, actual: u8 },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::BadMagic(magic) => write!(f, "bad frame magic: {:02x?}", magic),
+            FrameError::UnsupportedVersion { major, minor } => {
+                write!(f, "unsupported wire version {}.{} (expected major {})", major, minor, WIRE_VERSION_MAJOR)
+            }
+            FrameError::InvalidHeaderLength(len) => write!(f, "invalid frame header length: {}", len),
+            FrameError::Truncated { needed, available } => {
+                write!(f, "truncated frame: need {} bytes, have {}", needed, available)
+            }
+            FrameError::Oversized { size, max } => write!(f, "frame of {} bytes exceeds limit of {} bytes", size, max),
+            FrameError::ChecksumMismatch { expected, actual } => {
+                write!(f, "frame checksum mismatch: expected {:08x}, got {:08x}", expected, actual)
+            }
+            FrameError::UnknownSerialization(id) => write!(f, "unknown serialization id: {}", id),
+            FrameError::UnknownCodec(id) => write!(f, "unknown codec id: {}", id),
+            FrameError::Serialization(msg) => write!(f, "frame payload serialization failed: {}", msg),
+            FrameError::UnexpectedKind { expected, actual } => {
+                write!(f, "unexpected frame kind {} (expected {})", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl FrameError {
+    /// 稳定的数值错误码（11xx）
+    pub fn code(&self) -> u32 {
+        match self {
+            FrameError::BadMagic(_) => 1101,
+            FrameError::UnsupportedVersion { .. } => 1102,
+            FrameError::InvalidHeaderLength(_) => 1103,
+            FrameError::Truncated { .. } => 1104,
+            FrameError::Oversized { .. } => 1105,
+            FrameError::ChecksumMismatch { .. } => 1106,
+            FrameError::UnknownSerialization(_) => 1107,
+            FrameError::UnknownCodec(_) => 1108,
+            FrameError::Serialization(_) => 1109,
+            FrameError::UnexpectedKind { .. } => 1110,
+        }
+    }
+}
+
+/// 帧中携带的消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    /// 单个 `EventMessage`
+    Event,
+    /// `EventBatch`
+    Batch,
+    /// 客户端发送的 `ControlMessage`
+    Control,
+    /// 服务器发送的 `ServerMessage`
+    Server,
+}
+
+impl FrameKind {
+    pub fn id(&self) -> u8 {
+        match self {
+            FrameKind::Event => 0,
+            FrameKind::Batch => 1,
+            FrameKind::Control => 2,
+            FrameKind::Server => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(FrameKind::Event),
+            1 => Some(FrameKind::Batch),
+            2 => Some(FrameKind::Control),
+            3 => Some(FrameKind::Server),
+            _ => None,
+        }
+    }
+}
+
+/// 可以编码为帧的消息
+pub trait WireMessage: Serialize + DeserializeOwned {
+    /// 帧标志中记录的消息类型
+    const KIND: FrameKind;
+
+    /// 负载使用的序列化协议（`Auto` 按 Bincode 处理）
+    fn serialization_format(&self) -> SerializationProtocol;
+
+    /// 消息内数据使用的编解码器编号
+    fn codec_id(&self) -> u8;
+
+    /// 解码后按帧头记录编解码器编号（编号不参与负载序列化的消息需要实现）
+    fn set_codec_id(&mut self, _codec_id: u8) {}
+
+    /// 除消息类型外的帧标志（`FLAG_COMPRESSED`、`FLAG_DICTIONARY`）
+    fn data_flags(&self) -> u8;
+
+    /// 按旧次版本的布局解码 Bincode 负载，布局未变化时返回 None（按当前布局解码）
+    fn from_legacy_bincode(_payload: &[u8], _version_minor: u8) -> Option<Result<Self, FrameError>> {
+        None
+    }
+}
+
+impl WireMessage for EventMessage {
+    const KIND: FrameKind = FrameKind::Event;
+
+    fn serialization_format(&self) -> SerializationProtocol {
+        self.serialization_format
+    }
+
+    fn codec_id(&self) -> u8 {
+        self.codec_id.unwrap_or_else(|| CompressionType::from(self.compression_format).wire_id())
+    }
+
+    fn set_codec_id(&mut self, codec_id: u8) {
+        let builtin = CompressionType::from(self.compression_format).wire_id();
+        self.codec_id = (codec_id != builtin).then_some(codec_id);
+    }
+
+    fn data_flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.is_compressed {
+            flags |= FLAG_COMPRESSED;
+        }
+        if self.dictionary_id.is_some() {
+            flags |= FLAG_DICTIONARY;
+        }
+        flags
+    }
+
+    fn from_legacy_bincode(payload: &[u8], version_minor: u8) -> Option<Result<Self, FrameError>> {
+        (version_minor == 0).then(|| {
+            EventMessage::from_bincode_v0(payload).map_err(|e| FrameError::Serialization(e.to_string()))
+        })
+    }
+}
+
+/// 帧头
+///
+/// 布局（小端）：magic(4) | major(1) | minor(1) | header_len(1) | flags(1)
+/// | codec_id(1) | serialization_id(1) | payload_len(4) | checksum(4)
+///
+/// `header_len` 允许后续次版本在帧头末尾追加字段，旧版本解码时直接跳过。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version_major: u8,
+    pub version_minor: u8,
+    pub header_len: u8,
+    pub flags: u8,
+    pub codec_id: u8,
+    pub serialization_id: u8,
+    pub payload_len: u32,
+    pub checksum: u32, // 负载的 CRC32
+}
+
+impl FrameHeader {
+    /// 为给定负载创建当前版本的帧头
+    pub fn new(payload: &[u8], flags: u8, codec_id: u8, serialization_id: u8) -> Result<Self, FrameError> {
+        let payload_len = u32::try_from(payload.len()).map_err(|_| FrameError::Oversized {
+            size: payload.len(),
+            max: u32::MAX as usize,
+        })?;
+
+        Ok(Self {
+            version_major: WIRE_VERSION_MAJOR,
+            version_minor: WIRE_VERSION_MINOR,
+            header_len: FRAME_HEADER_LEN as u8,
+            flags,
+            codec_id,
+            serialization_id,
+            payload_len,
+            checksum: crc32fast::hash(payload),
+        })
+    }
+
+    /// 从字节中解析帧头（只需要固定长度部分）
+    pub fn parse(bytes: &[u8]) -> Result<Self, FrameError> {
+        if bytes.len() < FRAME_HEADER_LEN {
+            return Err(FrameError::Truncated { needed: FRAME_HEADER_LEN, available: bytes.len() });
+        }
+
+        let magic = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if magic != FRAME_MAGIC {
+            return Err(FrameError::BadMagic(magic));
+        }
+
+        let header = Self {
+            version_major: bytes[4],
+            version_minor: bytes[5],
+            header_len: bytes[6],
+            flags: bytes[7],
+            codec_id: bytes[8],
+            serialization_id: bytes[9],
+            payload_len: u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]),
+            checksum: u32::from_le_bytes([bytes[14], bytes[15], bytes[16], bytes[17]]),
+        };
+
+        if header.version_major != WIRE_VERSION_MAJOR {
+            return Err(FrameError::UnsupportedVersion {
+                major: header.version_major,
+                minor: header.version_minor,
+            });
+        }
+        if (header.header_len as usize) < FRAME_HEADER_LEN {
+            return Err(FrameError::InvalidHeaderLength(header.header_len));
+        }
+
+        Ok(header)
+    }
+
+    /// 将帧头写入缓冲区
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&FRAME_MAGIC);
+        out.push(self.version_major);
+        out.push(self.version_minor);
+        out.push(self.header_len);
+        out.push(self.flags);
+        out.push(self.codec_id);
+        out.push(self.serialization_id);
+        out.extend_from_slice(&self.payload_len.to_le_bytes());
+        out.extend_from_slice(&self.checksum.to_le_bytes());
+    }
+
+    /// 整个帧（帧头 + 负载）的长度
+    pub fn frame_len(&self) -> usize {
+        self.header_len as usize + self.payload_len as usize
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.flags & FLAG_COMPRESSED != 0
+    }
+
+    pub fn uses_dictionary(&self) -> bool {
+        self.flags & FLAG_DICTIONARY != 0
+    }
+
+    /// 帧中消息类型编号
+    pub fn kind_id(&self) -> u8 {
+        self.flags >> FRAME_KIND_SHIFT
+    }
+
+    /// 帧中消息类型（未知类型返回 None）
+    pub fn kind(&self) -> Option<FrameKind> {
+        FrameKind::from_id(self.kind_id())
+    }
+
+    /// 帧头声明的序列化协议
+    pub fn serialization(&self) -> Result<SerializationProtocol, FrameError> {
+        match SerializationProtocol::from_wire_id(self.serialization_id) {
+            Some(SerializationProtocol::Auto) | None => Err(FrameError::UnknownSerialization(self.serialization_id)),
+            Some(protocol) => Ok(protocol),
+        }
+    }
+}
+
+/// 将事件消息编码为带版本帧头的二进制帧
+///
+/// 负载使用消息自身的 `serialization_format`（`Auto` 按 Bincode 处理）。
+pub fn encode_frame(message: &EventMessage) -> Result<Vec<u8>, FrameError> {
+    encode_message(message)
+}
+
+/// 从字节开头解码一个完整帧，返回事件消息及消耗的字节数
+pub fn decode_frame(bytes: &[u8]) -> Result<(EventMessage, usize), FrameError> {
+    decode_message(bytes)
+}
+
+/// 将任意帧消息编码为二进制帧
+pub fn encode_message<M: WireMessage>(message: &M) -> Result<Vec<u8>, FrameError> {
+    let serialization = message.serialization_format().resolve();
+    let payload = serialize_payload(message, serialization)?;
+
+    let flags = (M::KIND.id() << FRAME_KIND_SHIFT) | message.data_flags();
+    let header = FrameHeader::new(&payload, flags, message.codec_id(), serialization.wire_id())?;
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    header.write_to(&mut frame);
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// 从字节开头解码一个完整帧，返回消息及消耗的字节数
+///
+/// 帧头中的编解码器编号按 `CodecRegistry::global()` 校验，使用自定义编解码器时改用 `decode_message_with_codecs`。
+pub fn decode_message<M: WireMessage>(bytes: &[u8]) -> Result<(M, usize), FrameError> {
+    decode_message_with_codecs(bytes, CodecRegistry::global())
+}
+
+/// 从字节开头解码一个完整帧，编解码器编号必须已在 `codecs` 中注册
+pub fn decode_message_with_codecs<M: WireMessage>(bytes: &[u8], codecs: &CodecRegistry) -> Result<(M, usize), FrameError> {
+    let header = FrameHeader::parse(bytes)?;
+    let frame_len = header.frame_len();
+    if bytes.len() < frame_len {
+        return Err(FrameError::Truncated { needed: frame_len, available: bytes.len() });
+    }
+
+    let payload = &bytes[header.header_len as usize..frame_len];
+    let message = decode_payload(&header, payload, codecs)?;
+    Ok((message, frame_len))
+}
+
+/// 流式帧解码器
+///
+/// 接收任意切分的字节片段（例如 QUIC 流的分块读取），在帧完整时产出事件消息。
+/// 帧头错误或超长帧意味着流已失去同步，调用方应关闭连接；
+/// 校验和或负载反序列化错误只会丢弃当前帧，解码器可以继续使用。
+#[derive(Debug)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_frame_size: usize,
+    /// 用于校验帧头编解码器编号的注册表
+    codecs: CodecRegistry,
+    /// `decode` 遇到的错误，先返回已解码的消息，下次调用时再报告
+    pending_error: Option<FrameError>,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    /// 使用默认最大帧长度创建解码器
+    pub fn new() -> Self {
+        Self::with_max_frame_size(DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// 使用指定的最大帧长度创建解码器
+    pub fn with_max_frame_size(max_frame_size: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_frame_size,
+            codecs: CodecRegistry::global().clone(),
+            pending_error: None,
+        }
+    }
+
+    /// 使用包含自定义编解码器的注册表（默认为 `CodecRegistry::global()`）
+    pub fn with_codecs(mut self, codecs: CodecRegistry) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    pub fn codecs(&self) -> &CodecRegistry {
+        &self.codecs
+    }
+
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// 当前缓冲但尚未解码的字节数
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// 追加读取到的字节
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// 尝试解码下一个完整的事件消息，数据不足时返回 `Ok(None)`
+    pub fn next_message(&mut self) -> Result<Option<EventMessage>, FrameError> {
+        self.next_typed()
+    }
+
+    /// 下一个帧的消息类型（帧头尚未完整时返回 None）
+    pub fn peek_kind(&self) -> Option<FrameKind> {
+        FrameHeader::parse(&self.buffer).ok().and_then(|header| header.kind())
+    }
+
+    /// 下一个帧的帧头（帧头尚未完整时返回 `Ok(None)`）
+    pub fn peek_header(&self) -> Result<Option<FrameHeader>, FrameError> {
+        match FrameHeader::parse(&self.buffer) {
+            Ok(header) => Ok(Some(header)),
+            Err(FrameError::Truncated { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 尝试解码下一个指定类型的完整消息，数据不足时返回 `Ok(None)`
+    ///
+    /// 下一个帧的类型不是 `M` 时返回 `UnexpectedKind` 并保留该帧，可以改用其他类型读取。
+    pub fn next_typed<M: WireMessage>(&mut self) -> Result<Option<M>, FrameError> {
+        let header = match self.peek_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        let frame_len = header.frame_len();
+        if frame_len > self.max_frame_size {
+            return Err(FrameError::Oversized { size: frame_len, max: self.max_frame_size });
+        }
+        if self.buffer.len() < frame_len {
+            return Ok(None);
+        }
+        // 类型不符时保留该帧；校验和或负载错误时丢弃该帧
+        check_kind::<M>(&header)?;
+        let result = decode_payload(&header, &self.buffer[header.header_len as usize..frame_len], &self.codecs);
+        self.buffer.drain(..frame_len);
+        result.map(Some)
+    }
+
+    /// 追加字节并返回所有已完整的事件消息
+    ///
+    /// 遇到错误帧时停止解码：若之前已解码出消息则先返回这些消息，错误在下次调用时返回。
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<Vec<EventMessage>, FrameError> {
+        self.push(bytes);
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
+
+        let mut messages = Vec::new();
+        loop {
+            match self.next_message() {
+                Ok(Some(message)) => messages.push(message),
+                Ok(None) => return Ok(messages),
+                Err(e) if messages.is_empty() => return Err(e),
+                Err(e) => {
+                    self.pending_error = Some(e);
+                    return Ok(messages);
+                }
+            }
+        }
+    }
+
+    /// 尝试解码下一个服务器消息，数据不足时返回 `Ok(None)`
+    ///
+    /// 单独发送的事件帧和批量帧会包装为对应的 `ServerMessage`，
+    /// 服务器可以继续用更紧凑的事件帧发送数据，只在控制应答时使用 `Server` 帧。
+    /// 其他类型的帧返回 `UnexpectedKind` 并保留在缓冲区中。
+    pub fn next_server_message(&mut self) -> Result<Option<ServerMessage>, FrameError> {
+        let header = match self.peek_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        match header.kind() {
+            Some(FrameKind::Event) => Ok(self.next_typed::<EventMessage>()?.map(ServerMessage::Event)),
+            Some(FrameKind::Batch) => Ok(self.next_typed::<EventBatch>()?.map(ServerMessage::Batch)),
+            _ => self.next_typed::<ServerMessage>(),
+        }
+    }
+
+    /// 流结束时调用，若仍有未报告的错误或残留的不完整帧则返回错误
+    pub fn finish(self) -> Result<(), FrameError> {
+        if let Some(e) = self.pending_error {
+            return Err(e);
+        }
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let needed = match FrameHeader::parse(&self.buffer) {
+            Ok(header) => header.frame_len(),
+            Err(FrameError::Truncated { needed, .. }) => needed,
+            Err(e) => return Err(e),
+        };
+        Err(FrameError::Truncated { needed, available: self.buffer.len() })
+    }
+}
+
+/// 校验并反序列化帧负载
+fn decode_payload<M: WireMessage>(header: &FrameHeader, payload: &[u8], codecs: &CodecRegistry) -> Result<M, FrameError> {
+    check_kind::<M>(header)?;
+
+    let actual = crc32fast::hash(payload);
+    if actual != header.checksum {
+        return Err(FrameError::ChecksumMismatch { expected: header.checksum, actual });
+    }
+    if !codecs.contains(header.codec_id) {
+        return Err(FrameError::UnknownCodec(header.codec_id));
+    }
+
+    let serialization = header.serialization()?;
+    let legacy = if serialization == SerializationProtocol::Bincode && header.version_minor < WIRE_VERSION_MINOR {
+        M::from_legacy_bincode(payload, header.version_minor)
+    } else {
+        None
+    };
+    let mut message: M = match legacy {
+        Some(result) => result?,
+        None => deserialize_payload(payload, serialization)?,
+    };
+    message.set_codec_id(header.codec_id);
+    Ok(message)
+}
+
+fn check_kind<M: WireMessage>(header: &FrameHeader) -> Result<(), FrameError> {
+    if header.kind_id() != M::KIND.id() {
+        return Err(FrameError::UnexpectedKind { expected: M::KIND.id(), actual: header.kind_id() });
+    }
+    Ok(())
+}
+
+fn serialize_payload<M: Serialize>(message: &M, serialization: SerializationProtocol) -> Result<Vec<u8>, FrameError> {
+    match serialization {
+        SerializationProtocol::JSON => {
+            serde_json::to_vec(message).map_err(|e| FrameError::Serialization(e.to_string()))
+        }
+        SerializationProtocol::Bincode | SerializationProtocol::Auto => {
+            bincode::serialize(message).map_err(|e| FrameError::Serialization(e.to_string()))
+        }
+    }
+}
+
+fn deserialize_payload<M: DeserializeOwned>(payload: &[u8], serialization: SerializationProtocol) -> Result<M, FrameError> {
+    match serialization {
+        SerializationProtocol::JSON => {
+            serde_json::from_slice(payload).map_err(|e| FrameError::Serialization(e.to_string()))
+        }
+        SerializationProtocol::Bincode | SerializationProtocol::Auto => {
+            bincode::deserialize(payload).map_err(|e| FrameError::Serialization(e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use fzstream_common::{ApiKeyManager, AuthErrorCode, AuthTokenValidator, MemoryApiKeyStore};
+
+fn manager() -> ApiKeyManager {
+    ApiKeyManager::new(Arc::new(MemoryApiKeyStore::new()))
+}
+
+#[test]
+fn created_key_grants_its_permissions() {
+    let api_keys = manager();
+    let issued = api_keys.create("bot", vec!["stream".to_string()], None).unwrap();
+    let validator = AuthTokenValidator::new().with_api_keys(api_keys.clone());
+
+    assert_eq!(validator.validate_token(&issued.key).unwrap(), vec!["stream".to_string()]);
+
+    api_keys
+        .set_permissions(&issued.record.key_id, vec!["read".to_string()])
+        .unwrap();
+    assert_eq!(validator.validate_token(&issued.key).unwrap(), vec!["read".to_string()]);
+}
+
+#[test]
+fn guessed_or_tampered_keys_are_rejected() {
+    let api_keys = manager();
+    let issued = api_keys.create("bot", vec!["stream".to_string()], None).unwrap();
+    let validator = AuthTokenValidator::new().with_api_keys(api_keys);
+
+    let malformed = validator.validate_token("sk_user_123").unwrap_err();
+    assert_eq!(malformed.auth_code(), Some(AuthErrorCode::MalformedToken));
+
+    let mut tampered = issued.key.clone();
+    let last = if tampered.ends_with('0') { "1" } else { "0" };
+    tampered.replace_range(tampered.len() - 1.., last);
+    let err = validator.validate_token(&tampered).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+
+    assert!(AuthTokenValidator::new().validate_token(&issued.key).is_err());
+}
+
+#[test]
+fn rotation_expires_old_key_and_revocation_removes_it() {
+    let api_keys = manager();
+    let old = api_keys.create("bot", vec!["stream".to_string()], None).unwrap();
+    let new = api_keys.rotate(&old.record.key_id, Duration::ZERO).unwrap();
+
+    assert_eq!(new.record.permissions, old.record.permissions);
+    let expired = api_keys.verify(&old.key).unwrap_err();
+    assert_eq!(expired.auth_code(), Some(AuthErrorCode::ExpiredToken));
+    assert!(api_keys.verify(&new.key).is_ok());
+
+    assert!(api_keys.revoke(&new.record.key_id).unwrap());
+    assert!(api_keys.verify(&new.key).is_err());
+}
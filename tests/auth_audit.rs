@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use fzstream_common::{
+    ApiKeyManager, AuditSink, AuditSubject, AuthAuditEvent, AuthErrorCode, AuthMessage, AuthOutcome,
+    AuthTokenValidator, CredentialRecord, FzStreamError, JsonLinesAuditSink, JwtKey, JwtVerifier, MemoryApiKeyStore,
+    MemoryAuditSink, MemoryCredentialStore, TokenIssuer, TokenKind,
+};
+
+#[test]
+fn validator_records_success_and_failure() {
+    let store = MemoryCredentialStore::new();
+    store.insert(CredentialRecord::new("ops", "ops-token", vec!["stream".to_string()]));
+    let sink = Arc::new(MemoryAuditSink::new());
+    let validator = AuthTokenValidator::new()
+        .with_credential_store(Arc::new(store))
+        .with_jwt_verifier(JwtVerifier::hs256(b"secret"))
+        .with_audit_sink(sink.clone());
+
+    validator.authenticate(&AuthMessage::new("ops-token", "client-1", None)).unwrap();
+    let forged = TokenIssuer::hs256(b"other")
+        .issue("user-1", vec!["stream".to_string()], Duration::from_secs(60), None)
+        .unwrap();
+    assert!(validator.authenticate(&AuthMessage::new(forged, "client-2", None)).is_err());
+    assert!(validator.validate_token("garbage").is_err());
+
+    let events = sink.events();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].client_id.as_deref(), Some("client-1"));
+    assert_eq!(events[0].token_kind, TokenKind::Credential);
+    assert_eq!(events[0].outcome, AuthOutcome::Success);
+    assert_eq!(events[0].permissions, vec!["stream".to_string()]);
+    assert_eq!(events[0].subject.subject.as_deref(), Some("ops"));
+
+    assert_eq!(events[1].token_kind, TokenKind::Jwt);
+    assert_eq!(events[1].outcome, AuthOutcome::Failure);
+    assert_eq!(events[1].code, Some(AuthErrorCode::InvalidToken.code()));
+    assert!(events[1].permissions.is_empty());
+    assert_eq!(events[1].subject, AuditSubject::default());
+
+    assert_eq!(events[2].client_id, None);
+    assert_eq!(events[2].token_kind, TokenKind::Unknown);
+}
+
+#[test]
+fn audit_events_record_the_verified_subject() {
+    let api_keys = ApiKeyManager::new(Arc::new(MemoryApiKeyStore::new()));
+    let issued = api_keys.create("bot", vec!["read".to_string()], None).unwrap();
+    let sink = Arc::new(MemoryAuditSink::new());
+    let validator = AuthTokenValidator::new()
+        .with_api_keys(api_keys)
+        .with_jwt_verifier(JwtVerifier::new().with_key(JwtKey::hs256(b"secret").with_kid("k1")))
+        .with_audit_sink(sink.clone());
+
+    validator.validate_token(&issued.key).unwrap();
+    let token = TokenIssuer::hs256(b"secret")
+        .issue("user-1", vec!["stream".to_string()], Duration::from_secs(60), None)
+        .unwrap();
+    let claims = validator.validate_jwt(&token).unwrap();
+    assert!(validator.validate_jwt("a.b.c").is_err());
+
+    let events = sink.events();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].token_kind, TokenKind::ApiKey);
+    assert_eq!(events[0].subject.subject.as_deref(), Some("bot"));
+    assert_eq!(events[0].subject.credential_id.as_deref(), Some(issued.record.key_id.as_str()));
+
+    // validate_jwt 同样记录审计事件
+    assert_eq!(events[1].token_kind, TokenKind::Jwt);
+    assert_eq!(events[1].subject.subject.as_deref(), Some("user-1"));
+    assert_eq!(events[1].subject.credential_id, claims.token_id);
+    assert_eq!(events[1].subject.key_id.as_deref(), Some("k1"));
+    assert_eq!(events[2].outcome, AuthOutcome::Failure);
+}
+
+#[test]
+fn json_lines_sink_appends_one_event_per_line() {
+    let path = std::env::temp_dir().join(format!("fzstream-audit-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let ok = AuthAuditEvent::from_result(Some("client-1"), TokenKind::ApiKey, &Ok(vec!["read".to_string()]))
+        .with_subject(AuditSubject {
+            subject: Some("bot".to_string()),
+            credential_id: Some("k1".to_string()),
+            key_id: None,
+        });
+    JsonLinesAuditSink::open(&path).unwrap().record(&ok).unwrap();
+    let expired = Err(FzStreamError::auth(AuthErrorCode::ExpiredToken, "session expired"));
+    let err = AuthAuditEvent::from_result(Some("client-1"), TokenKind::Session, &expired);
+    JsonLinesAuditSink::open(&path).unwrap().record(&err).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let events: Vec<AuthAuditEvent> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(events, vec![ok, err]);
+    assert!(content.contains(r#""token_kind":"api_key""#));
+    assert!(content.contains(r#""subject":"bot","credential_id":"k1""#));
+
+    std::fs::remove_file(&path).unwrap();
+}
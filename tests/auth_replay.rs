@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use fzstream_common::{
+    AuthErrorCode, AuthMessage, AuthReplayGuard, AuthResponse, AuthTokenValidator, CredentialRecord, EventType,
+    EventTypeFilter, Hello, MemoryCredentialStore,
+};
+
+fn message_at(timestamp: u64, nonce: Option<&str>) -> AuthMessage {
+    AuthMessage {
+        timestamp,
+        nonce: nonce.map(str::to_string),
+        ..AuthMessage::new("token", "client-1", None)
+    }
+}
+
+#[test]
+fn fresh_messages_pass_once() {
+    let guard = AuthReplayGuard::default();
+    let message = AuthMessage::new("token", "client-1", None);
+
+    guard.check(&message).unwrap();
+    let err = guard.check(&message).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::ReplayDetected));
+
+    match AuthResponse::failure(&err) {
+        AuthResponse::Failure { code, .. } => assert_eq!(code, AuthErrorCode::ReplayDetected.code()),
+        other => panic!("unexpected response: {:?}", other),
+    }
+}
+
+#[test]
+fn timestamps_outside_window_are_rejected() {
+    let guard = AuthReplayGuard::new(Duration::from_secs(30));
+    let now = 1_000_000;
+
+    assert!(guard.check_at(&message_at(now - 30_000, Some("a")), now).is_ok());
+    assert!(guard.check_at(&message_at(now + 30_000, Some("b")), now).is_ok());
+
+    let old = guard.check_at(&message_at(now - 30_001, Some("c")), now).unwrap_err();
+    assert_eq!(old.auth_code(), Some(AuthErrorCode::StaleTimestamp));
+    let future = guard.check_at(&message_at(now + 30_001, Some("d")), now).unwrap_err();
+    assert_eq!(future.auth_code(), Some(AuthErrorCode::StaleTimestamp));
+}
+
+#[test]
+fn nonce_is_required_unless_disabled() {
+    let now = 1_000_000;
+    let err = AuthReplayGuard::default().check_at(&message_at(now, None), now).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::MissingNonce));
+
+    let lenient = AuthReplayGuard::default().with_require_nonce(false);
+    assert!(lenient.check_at(&message_at(now, None), now).is_ok());
+}
+
+#[test]
+fn nonces_are_forgotten_after_the_window() {
+    let guard = AuthReplayGuard::new(Duration::from_secs(30));
+    let now = 1_000_000;
+
+    guard.check_at(&message_at(now, Some("n")), now).unwrap();
+    assert_eq!(guard.cached_nonces(), 1);
+
+    guard.check_at(&message_at(now + 30_001, Some("other")), now + 30_001).unwrap();
+    assert_eq!(guard.cached_nonces(), 1);
+}
+
+#[test]
+fn full_nonce_cache_fails_closed() {
+    let guard = AuthReplayGuard::new(Duration::from_secs(30)).with_max_entries(3);
+    let now = 1_000_000;
+
+    guard.check_at(&message_at(now, Some("victim")), now).unwrap();
+    for nonce in ["flood-1", "flood-2"] {
+        guard.check_at(&message_at(now, Some(nonce)), now).unwrap();
+    }
+
+    // 缓存已满：新 nonce 被拒绝，旧 nonce 仍然被识别为重放
+    let err = guard.check_at(&message_at(now, Some("flood-3")), now).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::ReplayCacheFull));
+    let err = guard.check_at(&message_at(now, Some("victim")), now).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::ReplayDetected));
+    assert_eq!(guard.cached_nonces(), 3);
+
+    // 窗口过后记录被清理，新的认证恢复正常
+    let later = now + 30_001;
+    guard.check_at(&message_at(later, Some("fresh")), later).unwrap();
+    assert_eq!(guard.cached_nonces(), 1);
+}
+
+#[test]
+fn legacy_bincode_auth_message_is_decoded() {
+    #[derive(serde::Serialize)]
+    struct AuthMessageV0 {
+        auth_token: String,
+        client_id: String,
+        timestamp: u64,
+        event_filter: Option<EventTypeFilter>,
+    }
+
+    let legacy = bincode::serialize(&AuthMessageV0 {
+        auth_token: "token".to_string(),
+        client_id: "old-client".to_string(),
+        timestamp: 42,
+        event_filter: Some(EventTypeFilter::allow_only(vec![EventType::PumpFunBuy])),
+    })
+    .unwrap();
+    let message = AuthMessage::from_bincode(&legacy).unwrap();
+    assert_eq!(message.client_id, "old-client");
+    assert_eq!(message.timestamp, 42);
+    assert!(message.event_filter.is_some());
+    assert!(message.nonce.is_none());
+    assert!(message.hello.is_none());
+
+    let current = AuthMessage::new("token", "client-1", None).with_hello(Hello::default());
+    let decoded = AuthMessage::from_bincode(&bincode::serialize(&current).unwrap()).unwrap();
+    assert_eq!(decoded.nonce, current.nonce);
+    assert!(decoded.hello.is_some());
+
+    assert!(AuthMessage::from_bincode(b"garbage").is_err());
+}
+
+#[test]
+fn validator_rejects_replayed_auth_messages_before_the_token() {
+    let store = MemoryCredentialStore::new();
+    store.insert(CredentialRecord::new("ops", "ops-token", vec!["stream".to_string()]));
+    let validator = AuthTokenValidator::new()
+        .with_credential_store(Arc::new(store))
+        .with_replay_guard(AuthReplayGuard::default());
+
+    let message = AuthMessage::new("ops-token", "client-1", None);
+    assert_eq!(validator.authenticate(&message).unwrap(), vec!["stream".to_string()]);
+    let err = validator.authenticate(&message).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::ReplayDetected));
+
+    // 时间戳过期的消息即使令牌有效也被拒绝
+    let stale = message_at(1_000, Some("fresh"));
+    let stale = AuthMessage { auth_token: "ops-token".to_string(), ..stale };
+    let err = validator.authenticate(&stale).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::StaleTimestamp));
+
+    // 只验证令牌时不做重放检查
+    assert!(validator.validate_token("ops-token").is_ok());
+    assert_eq!(validator.replay_guard().unwrap().cached_nonces(), 1);
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use fzstream_common::{
+    decode_message_with_codecs, encode_frame, Codec, CodecRegistry, CompressionLevel, EventMessage, EventType,
+    FrameDecoder, FrameError, FrameHeader, FzResult, FzStreamError, SerializationProtocol, CODEC_ID_LZ4, CODEC_ID_NONE,
+    CODEC_ID_ZSTD, FRAME_HEADER_LEN,
+};
+
+const XOR_CODEC_ID: u8 = 200;
+const XOR_KEY: u8 = 0x5A;
+
+/// 测试用编解码器：逐字节异或
+struct XorCodec;
+
+impl Codec for XorCodec {
+    fn id(&self) -> u8 {
+        XOR_CODEC_ID
+    }
+
+    fn name(&self) -> &str {
+        "xor"
+    }
+
+    fn compress_into(&self, input: &[u8], _level: i32, output: &mut Vec<u8>) -> FzResult<()> {
+        output.extend(input.iter().map(|byte| byte ^ XOR_KEY));
+        Ok(())
+    }
+
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>, max_size: usize) -> FzResult<()> {
+        if input.len() > max_size {
+            return Err(FzStreamError::Decompression("too large".to_string()));
+        }
+        output.extend(input.iter().map(|byte| byte ^ XOR_KEY));
+        Ok(())
+    }
+
+    fn max_compressed_len(&self, input_len: usize) -> usize {
+        input_len
+    }
+}
+
+const RLE_CODEC_ID: u8 = 201;
+
+/// 测试用编解码器：游程编码，输出为（次数, 字节）对
+struct RleCodec;
+
+impl Codec for RleCodec {
+    fn id(&self) -> u8 {
+        RLE_CODEC_ID
+    }
+
+    fn name(&self) -> &str {
+        "rle"
+    }
+
+    fn compress_into(&self, input: &[u8], _level: i32, output: &mut Vec<u8>) -> FzResult<()> {
+        for run in input.chunk_by(|a, b| a == b) {
+            for chunk in run.chunks(u8::MAX as usize) {
+                output.extend([chunk.len() as u8, chunk[0]]);
+            }
+        }
+        Ok(())
+    }
+
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>, max_size: usize) -> FzResult<()> {
+        for pair in input.chunks(2) {
+            let (count, byte) = match pair {
+                [count, byte] => (*count as usize, *byte),
+                _ => return Err(FzStreamError::Decompression("truncated run".to_string())),
+            };
+            if output.len() + count > max_size {
+                return Err(FzStreamError::Decompression("too large".to_string()));
+            }
+            output.extend(std::iter::repeat_n(byte, count));
+        }
+        Ok(())
+    }
+
+    fn max_compressed_len(&self, input_len: usize) -> usize {
+        input_len * 2
+    }
+}
+
+fn registry_with_xor() -> CodecRegistry {
+    let mut registry = CodecRegistry::with_defaults();
+    assert!(registry.register(Arc::new(XorCodec)).is_none());
+    registry
+}
+
+/// 编码事件帧并把帧头中的编解码器编号改为 `codec_id`（校验和只覆盖负载，无需重算）
+fn frame_with_codec(codec_id: u8) -> Vec<u8> {
+    let message = EventMessage::new(
+        EventType::PumpFunBuy,
+        b"payload".to_vec(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::None,
+        false,
+    );
+    let frame = encode_frame(&message).unwrap();
+    let mut header = FrameHeader::parse(&frame).unwrap();
+    header.codec_id = codec_id;
+
+    let mut rewritten = Vec::new();
+    header.write_to(&mut rewritten);
+    rewritten.extend_from_slice(&frame[FRAME_HEADER_LEN..]);
+    rewritten
+}
+
+#[test]
+fn default_registry_contains_builtin_codecs() {
+    let registry = CodecRegistry::with_defaults();
+    let mut ids = registry.ids();
+    ids.sort_unstable();
+    assert_eq!(ids, [CODEC_ID_NONE, CODEC_ID_LZ4, CODEC_ID_ZSTD]);
+    assert_eq!(registry.get(CODEC_ID_ZSTD).unwrap().name(), "zstd");
+}
+
+#[test]
+fn custom_codec_roundtrips_through_registry() {
+    let registry = registry_with_xor();
+    assert!(registry.contains(XOR_CODEC_ID));
+    assert_eq!(registry.get(XOR_CODEC_ID).unwrap().name(), "xor");
+
+    let compressed = registry.compress(XOR_CODEC_ID, b"hello", 0).unwrap();
+    assert_ne!(compressed, b"hello");
+    assert_eq!(registry.decompress(XOR_CODEC_ID, &compressed, 1024).unwrap(), b"hello");
+    assert!(registry.decompress(XOR_CODEC_ID, &compressed, 2).is_err());
+}
+
+#[test]
+fn registering_same_id_replaces_codec() {
+    let mut registry = registry_with_xor();
+    let previous = registry.register(Arc::new(XorCodec)).unwrap();
+    assert_eq!(previous.id(), XOR_CODEC_ID);
+    assert_eq!(registry.ids().len(), 4);
+}
+
+#[test]
+fn unknown_codec_id_is_rejected() {
+    let registry = CodecRegistry::with_defaults();
+    assert!(registry.get(XOR_CODEC_ID).is_none());
+    assert!(!registry.contains(XOR_CODEC_ID));
+    assert!(matches!(registry.compress(XOR_CODEC_ID, b"x", 0), Err(FzStreamError::Compression(_))));
+    assert!(matches!(registry.decompress(XOR_CODEC_ID, b"x", 16), Err(FzStreamError::Decompression(_))));
+    assert!(CodecRegistry::new().ids().is_empty());
+}
+
+#[test]
+fn frame_decoder_accepts_codecs_from_its_registry() {
+    let frame = frame_with_codec(XOR_CODEC_ID);
+
+    let mut decoder = FrameDecoder::new();
+    assert_eq!(decoder.decode(&frame).unwrap_err(), FrameError::UnknownCodec(XOR_CODEC_ID));
+
+    let mut decoder = FrameDecoder::new().with_codecs(registry_with_xor());
+    let messages = decoder.decode(&frame).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].data, b"payload");
+
+    let (message, consumed) = decode_message_with_codecs::<EventMessage>(&frame, &registry_with_xor()).unwrap();
+    assert_eq!(consumed, frame.len());
+    assert_eq!(message.data, b"payload");
+}
+
+#[test]
+fn event_compressed_with_custom_codec_roundtrips_through_frames() {
+    let data = vec![7u8; 300];
+    let message =
+        EventMessage::new_with_codec(EventType::PumpFunBuy, data.clone(), SerializationProtocol::Bincode, &RleCodec, 0);
+    assert!(message.is_compressed);
+    assert_eq!(message.codec_id, Some(RLE_CODEC_ID));
+    assert_eq!(message.original_size, Some(data.len()));
+
+    let frame = encode_frame(&message).unwrap();
+    assert_eq!(FrameHeader::parse(&frame).unwrap().codec_id, RLE_CODEC_ID);
+
+    let mut registry = CodecRegistry::with_defaults();
+    registry.register(Arc::new(RleCodec));
+    let mut decoder = FrameDecoder::new().with_codecs(registry.clone());
+    let decoded = decoder.decode(&frame).unwrap().remove(0);
+    assert_eq!(decoded.codec_id, Some(RLE_CODEC_ID));
+    assert_eq!(decoded.get_decompressed_data_with_codecs(&registry).unwrap(), data);
+    // 全局注册表中没有该编解码器
+    assert!(matches!(decoded.get_decompressed_data(), Err(FzStreamError::Decompression(_))));
+
+    // 压缩后不更小时保留原始数据，但仍记录编解码器编号
+    let message =
+        EventMessage::new_with_codec(EventType::PumpFunBuy, b"abc".to_vec(), SerializationProtocol::Bincode, &RleCodec, 0);
+    assert!(!message.is_compressed);
+    assert_eq!(message.get_decompressed_data_with_codecs(&registry).unwrap(), b"abc");
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use fzstream_common::{
+    CompressionConfig, CompressionLevel, CompressionPolicy, EventMessage, EventType, SerializationProtocol,
+    POLICY_MIN_SAMPLES, POLICY_PROBE_INTERVAL,
+};
+
+fn config(algorithm: &str, level: u32) -> CompressionConfig {
+    CompressionConfig {
+        algorithm: algorithm.to_string(),
+        level,
+        threshold_size: 256,
+        max_compression_time_ms: 10,
+        ..CompressionConfig::default()
+    }
+}
+
+#[test]
+fn config_maps_to_compression_level() {
+    assert_eq!(config("lz4", 1).compression_level(), CompressionLevel::LZ4Fast);
+    assert_eq!(config("lz4", 9).compression_level(), CompressionLevel::LZ4High);
+    assert_eq!(config("ZSTD", 3).compression_level(), CompressionLevel::ZstdFast);
+    assert_eq!(config("zstd", 19).compression_level(), CompressionLevel::ZstdMax);
+    assert_eq!(config("snappy", 1).compression_level(), CompressionLevel::None);
+
+    let disabled = CompressionConfig {
+        enabled: false,
+        ..config("zstd", 3)
+    };
+    assert_eq!(disabled.compression_level(), CompressionLevel::None);
+}
+
+#[test]
+fn payloads_below_threshold_are_not_compressed() {
+    let policy = CompressionPolicy::new(config("zstd", 3));
+    assert_eq!(policy.select_level(&EventType::PumpFunBuy, 255), CompressionLevel::None);
+    assert_eq!(policy.select_level(&EventType::PumpFunBuy, 256), CompressionLevel::ZstdFast);
+
+    let message = EventMessage::new_with_policy(EventType::PumpFunBuy, vec![0; 100], SerializationProtocol::Bincode, &policy);
+    assert!(!message.is_compressed);
+}
+
+#[test]
+fn slow_compression_downgrades_to_faster_level() {
+    let policy = CompressionPolicy::new(config("zstd", 12));
+    let event_type = EventType::PumpFunBuy;
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::ZstdHigh);
+
+    policy.record(&event_type, CompressionLevel::ZstdHigh, 4096, 1024, Duration::from_millis(50));
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::ZstdMedium);
+
+    // 其他事件类型不受影响
+    assert_eq!(policy.select_level(&EventType::PumpFunSell, 4096), CompressionLevel::ZstdHigh);
+
+    policy.record(&event_type, CompressionLevel::ZstdMedium, 4096, 1024, Duration::from_millis(50));
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::ZstdFast);
+    policy.record(&event_type, CompressionLevel::ZstdFast, 4096, 1024, Duration::from_millis(50));
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::LZ4Fast);
+
+    // 最快的级别仍然超时则停止压缩
+    policy.record(&event_type, CompressionLevel::LZ4Fast, 4096, 1024, Duration::from_millis(50));
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::None);
+}
+
+#[test]
+fn fast_compression_keeps_configured_level() {
+    let policy = CompressionPolicy::new(config("lz4", 9));
+    let event_type = EventType::PumpFunBuy;
+
+    policy.record(&event_type, CompressionLevel::LZ4High, 4096, 1024, Duration::from_millis(1));
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::LZ4High);
+}
+
+#[test]
+fn incompressible_event_type_stops_compressing_and_is_probed() {
+    let policy = CompressionPolicy::new(config("lz4", 1));
+    let event_type = EventType::PumpFunBuy;
+
+    for _ in 0..POLICY_MIN_SAMPLES {
+        assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::LZ4Fast);
+        policy.record(&event_type, CompressionLevel::LZ4Fast, 4096, 4090, Duration::ZERO);
+    }
+    let stats = policy.stats();
+    assert!(!stats["PumpFunBuy"].compression_enabled);
+
+    // 停止压缩后每 POLICY_PROBE_INTERVAL 个事件重新尝试一次
+    for _ in 1..POLICY_PROBE_INTERVAL {
+        assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::None);
+    }
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::LZ4Fast);
+
+    // 本次尝试有收益时恢复压缩
+    policy.record(&event_type, CompressionLevel::LZ4Fast, 4096, 1024, Duration::ZERO);
+    assert!(policy.stats()["PumpFunBuy"].compression_enabled);
+    assert_eq!(policy.select_level(&event_type, 4096), CompressionLevel::LZ4Fast);
+}
+
+#[test]
+fn compressible_events_keep_compressing_through_policy() {
+    let policy = CompressionPolicy::new(config("zstd", 3));
+    let data = b"pumpfun buy event ".repeat(64);
+
+    for _ in 0..POLICY_MIN_SAMPLES * 2 {
+        let message =
+            EventMessage::new_with_policy(EventType::PumpFunBuy, data.clone(), SerializationProtocol::Bincode, &policy);
+        assert!(message.is_compressed);
+        assert_eq!(message.get_decompressed_data().unwrap(), data);
+    }
+
+    let stats = policy.stats();
+    let entry = &stats["PumpFunBuy"];
+    assert_eq!(entry.samples, POLICY_MIN_SAMPLES * 2);
+    assert!(entry.ratio() < 0.5);
+
+    policy.reset();
+    assert!(policy.stats().is_empty());
+}
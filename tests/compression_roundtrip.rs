@@ -0,0 +1,157 @@
+use fzstream_common::{
+    compress_data, decompress_data, decompress_data_with_limit, CompressionConfig, CompressionLevel, EventMessage,
+    EventType, FzStreamError, SerializationProtocol,
+};
+
+const ALL_LEVELS: [CompressionLevel; 7] = [
+    CompressionLevel::None,
+    CompressionLevel::LZ4Fast,
+    CompressionLevel::LZ4High,
+    CompressionLevel::ZstdFast,
+    CompressionLevel::ZstdMedium,
+    CompressionLevel::ZstdHigh,
+    CompressionLevel::ZstdMax,
+];
+
+/// 模拟序列化后的事件数据：有一定重复但压缩比不会太高
+fn sample_payload(len: usize) -> Vec<u8> {
+    let mut state: u32 = 0x1234_5678;
+    (0..len)
+        .map(|i| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            if i % 8 < 3 {
+                b"swap"[i % 4]
+            } else {
+                b'a' + ((state >> 16) % 16) as u8
+            }
+        })
+        .collect()
+}
+
+fn uncompressed_message(level: CompressionLevel) -> EventMessage {
+    EventMessage::new(EventType::PumpFunBuy, Vec::new(), SerializationProtocol::Bincode, level, false)
+}
+
+#[test]
+fn compress_data_output_decompresses_via_event_message() {
+    let payload = sample_payload(4096);
+
+    for level in ALL_LEVELS {
+        let compressed = compress_data(&payload, level).unwrap();
+
+        let mut message = uncompressed_message(level);
+        message.data = compressed;
+        message.is_compressed = level != CompressionLevel::None;
+        message.original_size = Some(payload.len());
+
+        assert_eq!(message.get_decompressed_data().unwrap(), payload, "level {:?}", level);
+    }
+}
+
+#[test]
+fn event_message_output_decompresses_via_decompress_data() {
+    let payload = sample_payload(4096);
+
+    for level in ALL_LEVELS {
+        let message = EventMessage::new(
+            EventType::PumpFunBuy,
+            payload.clone(),
+            SerializationProtocol::Bincode,
+            level,
+            true,
+        );
+        assert_eq!(message.is_compressed, level != CompressionLevel::None, "level {:?}", level);
+
+        let decompressed = if message.is_compressed {
+            decompress_data(&message.data, message.compression_format).unwrap()
+        } else {
+            message.data.clone()
+        };
+        assert_eq!(decompressed, payload, "level {:?}", level);
+    }
+}
+
+#[test]
+fn both_paths_produce_identical_output() {
+    let payload = sample_payload(2048);
+
+    for level in ALL_LEVELS.into_iter().filter(|l| *l != CompressionLevel::None) {
+        let message = EventMessage::new(
+            EventType::PumpFunBuy,
+            payload.clone(),
+            SerializationProtocol::Bincode,
+            level,
+            true,
+        );
+        assert_eq!(message.data, compress_data(&payload, level).unwrap(), "level {:?}", level);
+    }
+}
+
+#[test]
+fn lz4_high_differs_from_lz4_fast() {
+    let payload = sample_payload(8192);
+
+    let fast = compress_data(&payload, CompressionLevel::LZ4Fast).unwrap();
+    let high = compress_data(&payload, CompressionLevel::LZ4High).unwrap();
+    assert!(high.len() <= fast.len());
+    assert_ne!(fast, high);
+}
+
+#[test]
+fn highly_compressible_payload_roundtrips_without_original_size() {
+    let payload = vec![0u8; 1 << 20];
+
+    for level in ALL_LEVELS {
+        let compressed = compress_data(&payload, level).unwrap();
+        assert_eq!(decompress_data(&compressed, level).unwrap(), payload, "level {:?}", level);
+    }
+}
+
+#[test]
+fn decompression_limit_rejects_oversized_output() {
+    let payload = vec![0u8; 1 << 20];
+
+    for level in [CompressionLevel::LZ4Fast, CompressionLevel::ZstdFast] {
+        let compressed = compress_data(&payload, level).unwrap();
+        assert!(decompress_data_with_limit(&compressed, level, 1024).is_err(), "level {:?}", level);
+        assert!(decompress_data_with_limit(&compressed, level, payload.len()).is_ok(), "level {:?}", level);
+    }
+}
+
+#[test]
+fn configured_limit_rejects_decompression_bomb() {
+    // 64 MiB 的零压缩后只有几 KiB
+    let bomb = vec![0u8; 64 << 20];
+    let config = CompressionConfig {
+        max_decompressed_size: 1 << 20,
+        ..CompressionConfig::default()
+    };
+
+    for level in [CompressionLevel::LZ4Fast, CompressionLevel::ZstdFast] {
+        let compressed = compress_data(&bomb, level).unwrap();
+        assert!(compressed.len() < 1 << 20, "level {:?}", level);
+
+        let err = config.decompress(&compressed, level).unwrap_err();
+        assert!(matches!(err, FzStreamError::Decompression(_)), "level {:?}", level);
+
+        let mut message = uncompressed_message(level);
+        message.data = compressed;
+        message.is_compressed = true;
+        let err = message.get_decompressed_data_with_config(&config).unwrap_err();
+        assert!(matches!(err, FzStreamError::Decompression(_)), "level {:?}", level);
+    }
+}
+
+#[test]
+fn configured_limit_allows_output_within_limit() {
+    let payload = sample_payload(4096);
+    let config = CompressionConfig {
+        max_decompressed_size: payload.len(),
+        ..CompressionConfig::default()
+    };
+
+    let message =
+        EventMessage::new(EventType::PumpFunBuy, payload.clone(), SerializationProtocol::Bincode, CompressionLevel::ZstdFast, true);
+    assert_eq!(message.get_decompressed_data_with_config(&config).unwrap(), payload);
+    assert_eq!(config.decompress(&message.data, CompressionLevel::ZstdFast).unwrap(), payload);
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use fzstream_common::{
+    load_config, load_from_file, CompressionLevel, EnvOverrides, ServerConfig, StreamClientConfig,
+};
+
+fn vars(list: &[(&str, &str)]) -> Vec<(String, String)> {
+    list.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("fzstream-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn durations_use_human_readable_strings() {
+    let config = StreamClientConfig::default();
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(json["reconnect_interval"], "5s");
+
+    let mut legacy = json.clone();
+    legacy["reconnect_interval"] = serde_json::json!({ "secs": 7, "nanos": 0 });
+    legacy["connection_timeout"] = serde_json::json!(3);
+    legacy["keep_alive_interval"] = serde_json::json!("1m 30s");
+    let parsed: StreamClientConfig = serde_json::from_value(legacy).unwrap();
+    assert_eq!(parsed.reconnect_interval, Duration::from_secs(7));
+    assert_eq!(parsed.connection_timeout, Duration::from_secs(3));
+    assert_eq!(parsed.keep_alive_interval, Duration::from_secs(90));
+}
+
+#[test]
+fn durations_roundtrip_through_bincode() {
+    let config = StreamClientConfig {
+        connection_timeout: Duration::from_millis(1_500),
+        ..StreamClientConfig::default()
+    };
+    let bytes = bincode::serialize(&config).unwrap();
+    let decoded: StreamClientConfig = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded.connection_timeout, Duration::from_millis(1_500));
+    assert_eq!(decoded.reconnect_interval, config.reconnect_interval);
+}
+
+#[test]
+fn files_are_parsed_by_extension() {
+    let toml_path = temp_path("client.toml");
+    std::fs::write(&toml_path, "server_address = \"10.0.0.1:9000\"\nconnection_timeout = \"250ms\"\n").unwrap();
+    let config: StreamClientConfig = load_config(Some(&toml_path)).unwrap();
+    assert_eq!(config.server_address, "10.0.0.1:9000");
+    assert_eq!(config.connection_timeout, Duration::from_millis(250));
+    assert_eq!(config.compression, CompressionLevel::LZ4Fast);
+
+    let yaml_path = temp_path("server.yaml");
+    let full = serde_yaml::to_string(&ServerConfig { bind_address: "0.0.0.0:1".to_string(), ..ServerConfig::default() });
+    std::fs::write(&yaml_path, full.unwrap()).unwrap();
+    let server: ServerConfig = load_from_file(&yaml_path).unwrap();
+    assert_eq!(server.bind_address, "0.0.0.0:1");
+
+    assert!(load_from_file::<ServerConfig>(temp_path("server.ini")).is_err());
+    std::fs::remove_file(&toml_path).unwrap();
+    std::fs::remove_file(&yaml_path).unwrap();
+}
+
+#[test]
+fn environment_overrides_take_precedence() {
+    let config = ServerConfig::default()
+        .apply_overrides(vars(&[
+            ("FZSTREAM_SERVER_BIND_ADDRESS", "0.0.0.0:9000"),
+            ("FZSTREAM_SERVER_NUM_WORKERS", "8"),
+            ("FZSTREAM_SERVER_AUTH_SECRET_KEY", "12345"),
+            ("FZSTREAM_SERVER_ENABLE_STATS_REPORTER", "false"),
+            ("FZSTREAM_CLIENT_SERVER_NAME", "ignored"),
+        ]))
+        .unwrap();
+    assert_eq!(config.bind_address, "0.0.0.0:9000");
+    assert_eq!(config.num_workers, Some(8));
+    assert_eq!(config.auth_secret_key.as_deref(), Some("12345"));
+    assert!(!config.enable_stats_reporter);
+
+    let client = StreamClientConfig::default()
+        .apply_overrides(vars(&[("FZSTREAM_CLIENT_RECONNECT_INTERVAL", "2s"), ("FZSTREAM_CLIENT_COMPRESSION", "ZstdFast")]))
+        .unwrap();
+    assert_eq!(client.reconnect_interval, Duration::from_secs(2));
+    assert_eq!(client.compression, CompressionLevel::ZstdFast);
+
+    assert!(ServerConfig::default().apply_overrides(vars(&[("FZSTREAM_SERVER_BIND_ADRESS", "x")])).is_err());
+    assert!(ServerConfig::default().apply_overrides(vars(&[("FZSTREAM_SERVER_IDLE_TIMEOUT_SECS", "soon")])).is_err());
+}
@@ -0,0 +1,155 @@
+use fzstream_common::{
+    encode_frame, encode_message, AuthErrorCode, CloseReason, CompressionLevel, ControlMessage, EventBatchBuilder,
+    EventMessage, EventType, EventTypeFilter, FrameDecoder, FrameError, FrameKind, FzStreamError, ResumeRequest,
+    ResumeResponse, SerializationProtocol, ServerMessage,
+};
+
+fn decoder_with(frames: &[Vec<u8>]) -> FrameDecoder {
+    let mut decoder = FrameDecoder::new();
+    for frame in frames {
+        decoder.push(frame);
+    }
+    decoder
+}
+
+#[test]
+fn client_control_messages_roundtrip() {
+    let messages = [
+        ControlMessage::Subscribe { event_types: vec![EventType::PumpFunBuy] },
+        ControlMessage::Ack { sequence: 42 },
+        ControlMessage::Resume(ResumeRequest { last_sequence: 7, last_slot: 100 }),
+        ControlMessage::close(CloseReason::Normal, "bye"),
+    ];
+    let frames: Vec<Vec<u8>> = messages.iter().map(|message| encode_message(message).unwrap()).collect();
+    let mut decoder = decoder_with(&frames);
+
+    assert_eq!(decoder.peek_kind(), Some(FrameKind::Control));
+    match decoder.next_typed::<ControlMessage>().unwrap().unwrap() {
+        ControlMessage::Subscribe { event_types } => assert_eq!(event_types, [EventType::PumpFunBuy]),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(decoder.next_typed().unwrap(), Some(ControlMessage::Ack { sequence: 42 })));
+    assert!(matches!(
+        decoder.next_typed().unwrap(),
+        Some(ControlMessage::Resume(ResumeRequest { last_sequence: 7, last_slot: 100 }))
+    ));
+    match decoder.next_typed::<ControlMessage>().unwrap().unwrap() {
+        ControlMessage::Close { reason, message } => {
+            assert_eq!(reason, CloseReason::Normal);
+            assert_eq!(message.as_deref(), Some("bye"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    decoder.finish().unwrap();
+}
+
+#[test]
+fn ping_is_answered_with_matching_pong() {
+    let ping = ControlMessage::ping(9);
+    match ping.pong_for() {
+        Some(ControlMessage::Pong { nonce, .. }) => assert_eq!(nonce, 9),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(ControlMessage::Ack { sequence: 1 }.pong_for().is_none());
+}
+
+#[test]
+fn server_control_replies_roundtrip_through_server_frames() {
+    let error = FzStreamError::auth(AuthErrorCode::PermissionDenied, "stream:pumpfun not granted");
+    let replies = [
+        ServerMessage::from(ControlMessage::Pong { nonce: 3, timestamp: 1 }),
+        ServerMessage::FilterUpdated { filter: EventTypeFilter::allow_only(vec![EventType::PumpFunSell]) },
+        ServerMessage::Resumed(ResumeResponse::Resumed { from_sequence: 8, replay_count: 3 }),
+        ServerMessage::error(&error),
+    ];
+    let frames: Vec<Vec<u8>> = replies.iter().map(|reply| encode_message(reply).unwrap()).collect();
+    let mut decoder = decoder_with(&frames);
+
+    assert_eq!(decoder.peek_kind(), Some(FrameKind::Server));
+    assert!(matches!(
+        decoder.next_server_message().unwrap(),
+        Some(ServerMessage::Control(ControlMessage::Pong { nonce: 3, .. }))
+    ));
+    match decoder.next_server_message().unwrap().unwrap() {
+        ServerMessage::FilterUpdated { filter } => assert_eq!(filter.allowed_types, [EventType::PumpFunSell]),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(
+        decoder.next_server_message().unwrap(),
+        Some(ServerMessage::Resumed(ResumeResponse::Resumed { from_sequence: 8, replay_count: 3 }))
+    ));
+    match decoder.next_server_message().unwrap().unwrap() {
+        ServerMessage::Error { code, message } => {
+            assert_eq!(code, 4030);
+            assert_eq!(message, error.to_string());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(decoder.next_server_message().unwrap().is_none());
+}
+
+#[test]
+fn event_and_batch_frames_are_wrapped_as_server_messages() {
+    let event = EventMessage::new(
+        EventType::PumpFunBuy,
+        b"event".to_vec(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::None,
+        false,
+    );
+    let mut builder = EventBatchBuilder::new(5, SerializationProtocol::Bincode, CompressionLevel::None);
+    builder.push(EventType::PumpFunSell, b"batched").unwrap();
+
+    let mut decoder = decoder_with(&[
+        encode_frame(&event).unwrap(),
+        encode_message(&builder.build()).unwrap(),
+        encode_message(&ServerMessage::from(event.clone())).unwrap(),
+    ]);
+
+    match decoder.next_server_message().unwrap().unwrap() {
+        ServerMessage::Event(message) => assert_eq!(message.event_id, event.event_id),
+        other => panic!("unexpected {:?}", other),
+    }
+    match decoder.next_server_message().unwrap().unwrap() {
+        ServerMessage::Batch(batch) => assert_eq!(batch.slot, 5),
+        other => panic!("unexpected {:?}", other),
+    }
+    match decoder.next_server_message().unwrap().unwrap() {
+        ServerMessage::Event(message) => assert_eq!(message.data, b"event"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn control_frame_is_not_consumed_as_server_message() {
+    let frame = encode_message(&ControlMessage::Ack { sequence: 1 }).unwrap();
+    let mut decoder = decoder_with(std::slice::from_ref(&frame));
+
+    let err = decoder.next_server_message().unwrap_err();
+    assert_eq!(
+        err,
+        FrameError::UnexpectedKind { expected: FrameKind::Server.id(), actual: FrameKind::Control.id() }
+    );
+    assert_eq!(decoder.buffered_len(), frame.len());
+    assert!(matches!(decoder.next_typed().unwrap(), Some(ControlMessage::Ack { sequence: 1 })));
+}
+
+#[test]
+fn subscription_changes_apply_to_filter() {
+    let mut filter = EventTypeFilter::allow_only(vec![EventType::PumpFunBuy]);
+
+    assert!(ControlMessage::Subscribe { event_types: vec![EventType::PumpFunSell] }.apply_to_filter(&mut filter));
+    assert!(filter.is_allowed(&EventType::PumpFunSell));
+
+    assert!(ControlMessage::Unsubscribe { event_types: vec![EventType::PumpFunBuy] }.apply_to_filter(&mut filter));
+    assert!(!filter.is_allowed(&EventType::PumpFunBuy));
+
+    let mut all = EventTypeFilter::allow_all();
+    ControlMessage::Unsubscribe { event_types: vec![EventType::PumpFunBuy] }.apply_to_filter(&mut all);
+    assert!(!all.is_allowed(&EventType::PumpFunBuy));
+    assert!(all.is_allowed(&EventType::PumpFunSell));
+
+    let ack = ControlMessage::Ack { sequence: 1 };
+    assert!(!ack.is_filter_change());
+    assert!(!ack.apply_to_filter(&mut all));
+}
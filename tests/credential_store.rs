@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use fzstream_common::{
+    hash_token, AuthErrorCode, AuthTokenValidator, CredentialRecord, CredentialStore, FileCredentialStore,
+    MemoryCredentialStore,
+};
+
+#[test]
+fn validator_looks_up_hashed_credentials() {
+    let store = MemoryCredentialStore::new();
+    store.insert(CredentialRecord::new("ops", "ops-token", vec!["stream".to_string()]));
+    store.insert(CredentialRecord::new("old", "old-token", vec!["stream".to_string()]).with_expires_at(1));
+
+    let validator = AuthTokenValidator::new().with_credential_store(Arc::new(store));
+    assert_eq!(validator.validate_token("ops-token").unwrap(), vec!["stream".to_string()]);
+
+    let expired = validator.validate_token("old-token").unwrap_err();
+    assert_eq!(expired.auth_code(), Some(AuthErrorCode::ExpiredToken));
+    assert!(validator.validate_token("unknown-token").is_err());
+}
+
+#[cfg(not(feature = "dev-tokens"))]
+#[test]
+fn demo_tokens_are_disabled_by_default() {
+    assert!(AuthTokenValidator::new().validate_token("admin_super_token_999").is_err());
+}
+
+#[test]
+fn file_store_persists_only_hashes() {
+    let path = std::env::temp_dir().join(format!("fzstream-credentials-{}.json", std::process::id()));
+
+    let store = FileCredentialStore::create(&path).unwrap();
+    store
+        .insert(CredentialRecord::new("ops", "file-secret", vec!["read".to_string()]))
+        .unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(!content.contains("file-secret"));
+    assert!(content.contains(&hash_token("file-secret")));
+
+    let reopened = FileCredentialStore::open(&path).unwrap();
+    let record = reopened.lookup(&hash_token("file-secret")).unwrap().unwrap();
+    assert_eq!(record.name, "ops");
+
+    std::fs::remove_file(&path).unwrap();
+}
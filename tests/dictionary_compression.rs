@@ -0,0 +1,110 @@
+use fzstream_common::{
+    decode_frame, encode_frame, train_dictionary, CompressionLevel, DictionaryStore, EventMessage, EventType,
+    FrameHeader, FzStreamError, SerializationProtocol, ZstdDictionary,
+};
+
+/// 模拟结构相似的小事件
+fn sample_event(i: usize) -> Vec<u8> {
+    format!(
+        r#"{{"signature":"sig{:08}","mint":"So11111111111111111111111111111111111111112","sol_amount":{},"token_amount":{},"is_buy":{},"user":"user{:04}"}}"#,
+        i * 7919,
+        i * 1_000_003 % 99_999,
+        i * 31_337 % 1_000_000,
+        i.is_multiple_of(2),
+        i % 97
+    )
+    .into_bytes()
+}
+
+fn store_with_trained_dictionary() -> (DictionaryStore, u32) {
+    let samples: Vec<Vec<u8>> = (0..1000).map(sample_event).collect();
+    let dictionary = train_dictionary(&samples, 4096).unwrap();
+
+    let mut store = DictionaryStore::new();
+    let id = store.insert_bytes(dictionary).unwrap();
+    (store, id)
+}
+
+#[test]
+fn dictionary_compressed_event_roundtrips() {
+    let (store, id) = store_with_trained_dictionary();
+    let data = sample_event(5000);
+
+    let message = EventMessage::new_with_dictionary(
+        EventType::PumpFunBuy,
+        data.clone(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::ZstdFast,
+        &store,
+        id,
+    );
+    assert!(message.is_compressed);
+    assert_eq!(message.dictionary_id, Some(id));
+    assert!(message.data.len() < data.len());
+    assert_eq!(message.get_decompressed_data_with_dictionaries(&store).unwrap(), data);
+
+    // 没有字典时不能解压
+    assert!(matches!(message.get_decompressed_data(), Err(FzStreamError::Decompression(_))));
+}
+
+#[test]
+fn dictionary_compressed_event_roundtrips_through_frame() {
+    let (store, id) = store_with_trained_dictionary();
+    let data = sample_event(6000);
+    let message = EventMessage::new_with_dictionary(
+        EventType::PumpFunSell,
+        data.clone(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::ZstdMedium,
+        &store,
+        id,
+    );
+
+    let frame = encode_frame(&message).unwrap();
+    let header = FrameHeader::parse(&frame).unwrap();
+    assert!(header.is_compressed());
+    assert!(header.uses_dictionary());
+
+    let (decoded, _) = decode_frame(&frame).unwrap();
+    assert_eq!(decoded.dictionary_id, Some(id));
+    assert_eq!(decoded.get_decompressed_data_with_dictionaries(&store).unwrap(), data);
+}
+
+#[test]
+fn unknown_dictionary_id_is_rejected() {
+    let (store, id) = store_with_trained_dictionary();
+    let data = sample_event(7000);
+    let message = EventMessage::new_with_dictionary(
+        EventType::PumpFunBuy,
+        data,
+        SerializationProtocol::Bincode,
+        CompressionLevel::ZstdFast,
+        &store,
+        id,
+    );
+
+    let err = message.get_decompressed_data_with_dictionaries(&DictionaryStore::new()).unwrap_err();
+    assert!(matches!(err, FzStreamError::Decompression(_)));
+    assert!(err.to_string().contains("unknown dictionary id"));
+
+    // 编号相同但内容不同的字典无法正确解压
+    let mut other = DictionaryStore::new();
+    other.insert(ZstdDictionary::new(id, sample_event(1)));
+    assert!(message.get_decompressed_data_with_dictionaries(&other).is_err());
+}
+
+#[test]
+fn compressing_with_unknown_dictionary_keeps_data_uncompressed() {
+    let data = sample_event(8000);
+    let message = EventMessage::new_with_dictionary(
+        EventType::PumpFunBuy,
+        data.clone(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::ZstdFast,
+        &DictionaryStore::new(),
+        42,
+    );
+    assert!(!message.is_compressed);
+    assert_eq!(message.dictionary_id, None);
+    assert_eq!(message.data, data);
+}
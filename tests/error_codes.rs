@@ -0,0 +1,77 @@
+use fzstream_common::{
+    decode_frame, decompress_data, AuthErrorCode, AuthResponse, AuthTokenValidator, CompressionLevel, FrameError,
+    FzStreamError,
+};
+
+const ALL_AUTH_CODES: [AuthErrorCode; 12] = [
+    AuthErrorCode::InvalidToken,
+    AuthErrorCode::MalformedToken,
+    AuthErrorCode::ExpiredToken,
+    AuthErrorCode::TokenNotYetValid,
+    AuthErrorCode::StaleTimestamp,
+    AuthErrorCode::ReplayDetected,
+    AuthErrorCode::MissingNonce,
+    AuthErrorCode::TokenRevoked,
+    AuthErrorCode::ReplayCacheFull,
+    AuthErrorCode::PermissionDenied,
+    AuthErrorCode::RateLimited,
+    AuthErrorCode::ConnectionLimitExceeded,
+];
+
+#[test]
+fn auth_codes_are_stable_and_roundtrip() {
+    assert_eq!(AuthErrorCode::InvalidToken.code(), 4010);
+    assert_eq!(AuthErrorCode::PermissionDenied.code(), 4030);
+    assert_eq!(AuthErrorCode::RateLimited.code(), 4290);
+
+    for code in ALL_AUTH_CODES {
+        assert_eq!(AuthErrorCode::from_code(code.code()), Some(code));
+    }
+    assert_eq!(AuthErrorCode::from_code(1001), None);
+}
+
+#[test]
+fn error_variants_have_stable_codes() {
+    assert_eq!(FzStreamError::Compression("x".into()).code(), 1001);
+    assert_eq!(FzStreamError::Decompression("x".into()).code(), 1002);
+    assert_eq!(FzStreamError::Serialization("x".into()).code(), 1003);
+    assert_eq!(FzStreamError::Config("x".into()).code(), 1004);
+    assert_eq!(FzStreamError::Negotiation("x".into()).code(), 1005);
+    assert_eq!(FzStreamError::Signing("x".into()).code(), 1006);
+
+    let frame: FzStreamError = FrameError::UnknownCodec(9).into();
+    assert_eq!(frame.code(), 1108);
+    assert!(std::error::Error::source(&frame).is_some());
+
+    let auth = FzStreamError::auth(AuthErrorCode::ExpiredToken, "expired at 10");
+    assert_eq!(auth.code(), 4012);
+    assert_eq!(auth.auth_code(), Some(AuthErrorCode::ExpiredToken));
+    assert_eq!(auth.to_string(), "token expired: expired at 10");
+    assert_eq!(frame.auth_code(), None);
+}
+
+#[test]
+fn compression_and_frame_failures_are_typed() {
+    let err = decompress_data(b"not zstd data", CompressionLevel::ZstdFast).unwrap_err();
+    assert!(matches!(err, FzStreamError::Decompression(_)));
+    assert_eq!(err.code(), 1002);
+
+    let err: FzStreamError = decode_frame(b"XXXX").unwrap_err().into();
+    assert_eq!(err.code(), 1104);
+}
+
+#[test]
+fn invalid_token_failure_carries_code_in_auth_response() {
+    let validator = AuthTokenValidator::new();
+    let err = validator.validate_token("no-such-token").unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+
+    match AuthResponse::failure(&err) {
+        AuthResponse::Failure { error, code } => {
+            assert_eq!(code, 4010);
+            assert_eq!(AuthErrorCode::from_code(code), Some(AuthErrorCode::InvalidToken));
+            assert_eq!(error, err.to_string());
+        }
+        other => panic!("expected failure response, got {:?}", other),
+    }
+}
@@ -0,0 +1,65 @@
+use fzstream_common::{
+    negotiate, FzStreamError, Hello, ProtocolVersion, SerializationProtocol, CODEC_ID_LZ4, CODEC_ID_NONE, CODEC_ID_ZSTD,
+};
+
+fn hello(versions: &[(u8, u8)], serializations: &[SerializationProtocol], codecs: &[u8], dictionaries: &[u32]) -> Hello {
+    Hello {
+        protocol_versions: versions.iter().map(|&(major, minor)| ProtocolVersion::new(major, minor)).collect(),
+        serializations: serializations.to_vec(),
+        codecs: codecs.to_vec(),
+        dictionaries: dictionaries.to_vec(),
+    }
+}
+
+#[test]
+fn default_hellos_pick_server_preference() {
+    let welcome = negotiate(&Hello::new(), &Hello::new()).unwrap();
+
+    assert_eq!(welcome.protocol_version, ProtocolVersion::CURRENT);
+    assert_eq!(welcome.serialization, SerializationProtocol::Bincode);
+    assert_eq!(welcome.codec_id, CODEC_ID_ZSTD);
+    assert!(welcome.dictionaries.is_empty());
+}
+
+#[test]
+fn version_uses_highest_common_major_and_lower_minor() {
+    let client = hello(&[(1, 3), (2, 0)], &[SerializationProtocol::Bincode], &[], &[]);
+    let server = hello(&[(1, 1), (3, 0)], &[SerializationProtocol::Bincode], &[], &[]);
+
+    let welcome = negotiate(&client, &server).unwrap();
+    assert_eq!(welcome.protocol_version, ProtocolVersion::new(1, 1));
+
+    let incompatible = hello(&[(2, 0)], &[SerializationProtocol::Bincode], &[], &[]);
+    assert!(matches!(negotiate(&incompatible, &server), Err(FzStreamError::Negotiation(_))));
+}
+
+#[test]
+fn serialization_and_codec_follow_server_order_restricted_by_client() {
+    let client = hello(&[(1, 0)], &[SerializationProtocol::JSON], &[CODEC_ID_LZ4], &[]);
+    let server = hello(
+        &[(1, 0)],
+        &[SerializationProtocol::Bincode, SerializationProtocol::JSON],
+        &[CODEC_ID_ZSTD, CODEC_ID_LZ4],
+        &[],
+    );
+
+    let welcome = negotiate(&client, &server).unwrap();
+    assert_eq!(welcome.serialization, SerializationProtocol::JSON);
+    assert_eq!(welcome.codec_id, CODEC_ID_LZ4);
+
+    let auto_client = hello(&[(1, 0)], &[SerializationProtocol::Auto], &[200], &[]);
+    let welcome = negotiate(&auto_client, &server).unwrap();
+    assert_eq!(welcome.serialization, SerializationProtocol::Bincode);
+    assert_eq!(welcome.codec_id, CODEC_ID_NONE);
+}
+
+#[test]
+fn dictionaries_are_shared_only_with_zstd() {
+    let server = hello(&[(1, 0)], &[], &[CODEC_ID_ZSTD, CODEC_ID_LZ4], &[9, 3, 5]);
+
+    let zstd_client = hello(&[(1, 0)], &[], &[CODEC_ID_ZSTD], &[5, 7, 3]);
+    assert_eq!(negotiate(&zstd_client, &server).unwrap().dictionaries, vec![3, 5]);
+
+    let lz4_client = hello(&[(1, 0)], &[], &[CODEC_ID_LZ4], &[5, 7, 3]);
+    assert!(negotiate(&lz4_client, &server).unwrap().dictionaries.is_empty());
+}
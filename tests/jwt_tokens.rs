@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use fzstream_common::{
+    AuthErrorCode, AuthTokenValidator, FzStreamError, JwtVerifier, ServerConfig, TokenClaims, TokenIssuer,
+};
+
+fn server_config(secret: &str) -> ServerConfig {
+    ServerConfig {
+        auth_secret_key: Some(secret.to_string()),
+        ..ServerConfig::default()
+    }
+}
+
+#[test]
+fn issued_token_is_accepted_by_validator() {
+    let config = server_config("issuer-secret");
+    let issuer = TokenIssuer::from_server_config(&config).unwrap();
+    let validator = AuthTokenValidator::from_server_config(&config);
+
+    let permissions = vec!["read".to_string(), "stream".to_string()];
+    let token = issuer
+        .issue("user-1", permissions.clone(), Duration::from_secs(3600), Some("bot".to_string()))
+        .unwrap();
+
+    assert_eq!(validator.validate_token(&token).unwrap(), permissions);
+    let claims = validator.validate_jwt(&token).unwrap();
+    assert_eq!(claims.user_id, "user-1");
+    assert_eq!(claims.expires_at - claims.issued_at, 3600);
+    assert_eq!(claims.client_info.as_deref(), Some("bot"));
+}
+
+#[test]
+fn wrong_secret_is_rejected() {
+    let token = TokenIssuer::hs256(b"one")
+        .issue("user-1", vec!["read".to_string()], Duration::from_secs(60), None)
+        .unwrap();
+
+    let err = AuthTokenValidator::from_server_config(&server_config("two"))
+        .validate_token(&token)
+        .unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+}
+
+#[test]
+fn time_claims_respect_leeway() {
+    let issuer = TokenIssuer::hs256(b"secret");
+    let verifier = JwtVerifier::hs256(b"secret").with_leeway(30);
+    let token = issuer
+        .sign_claims(&TokenClaims {
+            user_id: "user-1".to_string(),
+            permissions: Vec::new(),
+            issued_at: 1_000,
+            expires_at: 2_000,
+            client_info: None,
+            quota: None,
+            token_id: None,
+            key_id: None,
+        })
+        .unwrap();
+
+    assert!(verifier.verify_at(&token, 2_030).is_ok());
+    let expired = verifier.verify_at(&token, 2_031).unwrap_err();
+    assert_eq!(expired.auth_code(), Some(AuthErrorCode::ExpiredToken));
+
+    assert!(verifier.verify_at(&token, 970).is_ok());
+    let early = verifier.verify_at(&token, 969).unwrap_err();
+    assert_eq!(early.auth_code(), Some(AuthErrorCode::TokenNotYetValid));
+}
+
+#[test]
+fn ed25519_tokens_verify_with_public_key() {
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+    let public_key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap().public_key().as_ref().to_vec();
+
+    let token = TokenIssuer::ed25519_from_pkcs8(pkcs8.as_ref())
+        .unwrap()
+        .with_kid("ed-1")
+        .issue("user-2", vec!["stream".to_string()], Duration::from_secs(60), None)
+        .unwrap();
+
+    let verifier = JwtVerifier::new().with_key(fzstream_common::JwtKey::ed25519_public_key(public_key).with_kid("ed-1"));
+    assert_eq!(verifier.verify(&token).unwrap().user_id, "user-2");
+}
+
+#[test]
+fn signing_failures_are_not_config_errors() {
+    let err = TokenIssuer::hs256(b"secret")
+        .issue("", Vec::new(), Duration::from_secs(60), None)
+        .unwrap_err();
+    assert!(matches!(err, FzStreamError::Signing(_)));
+    assert_eq!(err.code(), 1006);
+
+    // 无效的私钥属于配置错误
+    let err = TokenIssuer::rs256_from_pkcs8(b"not a key").unwrap_err();
+    assert!(matches!(err, FzStreamError::Config(_)));
+}
@@ -0,0 +1,189 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::hmac;
+use ring::rand::SystemRandom;
+use ring::signature::{self, Ed25519KeyPair, KeyPair, RsaKeyPair};
+use serde_json::{json, Value};
+
+use fzstream_common::{AuthErrorCode, JwtAlgorithm, JwtKey, JwtVerifier};
+
+/// 测试用 2048 位 RSA 私钥（PKCS#8 DER）
+const RSA_PKCS8: &[u8] = include_bytes!("data/rs256_test_key.pk8");
+
+const NOW: u64 = 1_700_000_000;
+
+fn claims() -> Value {
+    json!({ "sub": "user-1", "iat": NOW - 10, "exp": NOW + 3600, "permissions": ["read"] })
+}
+
+fn signing_input(header: &Value, claims: &Value) -> String {
+    format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(header).unwrap()),
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).unwrap())
+    )
+}
+
+fn with_signature(input: String, signature: &[u8]) -> String {
+    format!("{}.{}", input, URL_SAFE_NO_PAD.encode(signature))
+}
+
+fn hs256_token(secret: &[u8], header: Value, claims: &Value) -> String {
+    let input = signing_input(&header, claims);
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret), input.as_bytes());
+    with_signature(input, tag.as_ref())
+}
+
+fn rs256_token(key_pair: &RsaKeyPair, claims: &Value) -> String {
+    let input = signing_input(&json!({ "alg": "RS256", "typ": "JWT" }), claims);
+    let mut signature = vec![0; key_pair.public().modulus_len()];
+    key_pair
+        .sign(&signature::RSA_PKCS1_SHA256, &SystemRandom::new(), input.as_bytes(), &mut signature)
+        .unwrap();
+    with_signature(input, &signature)
+}
+
+fn ed25519_token(key_pair: &Ed25519KeyPair, claims: &Value) -> String {
+    let input = signing_input(&json!({ "alg": "EdDSA", "typ": "JWT" }), claims);
+    with_signature(input.clone(), key_pair.sign(input.as_bytes()).as_ref())
+}
+
+fn rsa_key_pair() -> RsaKeyPair {
+    RsaKeyPair::from_pkcs8(RSA_PKCS8).unwrap()
+}
+
+fn ed25519_key_pair() -> Ed25519KeyPair {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+    Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+}
+
+#[test]
+fn hs256_token_is_verified() {
+    let token = hs256_token(b"secret", json!({ "alg": "HS256", "typ": "JWT" }), &claims());
+    let claims = JwtVerifier::hs256(b"secret").verify_at(&token, NOW).unwrap();
+    assert_eq!(claims.user_id, "user-1");
+    assert_eq!(claims.permissions, ["read"]);
+    assert_eq!(claims.expires_at, NOW + 3600);
+
+    let err = JwtVerifier::hs256(b"other").verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+}
+
+#[test]
+fn rs256_token_is_verified_with_public_key() {
+    let key_pair = rsa_key_pair();
+    let public_key = KeyPair::public_key(&key_pair).as_ref().to_vec();
+    let token = rs256_token(&key_pair, &claims());
+
+    let verifier = JwtVerifier::new().with_key(JwtKey::rs256_public_key(public_key));
+    assert_eq!(verifier.verify_at(&token, NOW).unwrap().user_id, "user-1");
+
+    // 其他算法的密钥不会被用来验证 RS256 令牌
+    let err = JwtVerifier::hs256(b"secret").verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+}
+
+#[test]
+fn ed25519_token_is_verified_with_public_key() {
+    let key_pair = ed25519_key_pair();
+    let token = ed25519_token(&key_pair, &claims());
+
+    let key = JwtKey::ed25519_public_key(key_pair.public_key().as_ref().to_vec());
+    assert_eq!(key.algorithm(), JwtAlgorithm::EdDSA);
+    assert_eq!(JwtVerifier::new().with_key(key).verify_at(&token, NOW).unwrap().user_id, "user-1");
+
+    let other = JwtKey::ed25519_public_key(ed25519_key_pair().public_key().as_ref().to_vec());
+    let err = JwtVerifier::new().with_key(other).verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+}
+
+#[test]
+fn hs256_token_signed_with_rsa_public_key_is_rejected() {
+    // 算法混淆：用 RSA 公钥作为 HMAC 密钥伪造令牌
+    let public_key = KeyPair::public_key(&rsa_key_pair()).as_ref().to_vec();
+    let token = hs256_token(&public_key, json!({ "alg": "HS256" }), &claims());
+
+    let verifier = JwtVerifier::new().with_key(JwtKey::rs256_public_key(public_key));
+    let err = verifier.verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+}
+
+#[test]
+fn unsupported_algorithms_are_rejected() {
+    let input = signing_input(&json!({ "alg": "none" }), &claims());
+    let token = format!("{}.", input);
+    let err = JwtVerifier::hs256(b"secret").verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+    assert!(err.to_string().contains("unsupported JWT algorithm"));
+}
+
+#[test]
+fn kid_selects_matching_key() {
+    let verifier = JwtVerifier::new()
+        .with_key(JwtKey::hs256(b"first").with_kid("a"))
+        .with_key(JwtKey::hs256(b"second").with_kid("b"));
+
+    let token = hs256_token(b"second", json!({ "alg": "HS256", "kid": "b" }), &claims());
+    assert!(verifier.verify_at(&token, NOW).is_ok());
+
+    // 不带 kid 时尝试所有同算法的密钥
+    let token = hs256_token(b"first", json!({ "alg": "HS256" }), &claims());
+    assert!(verifier.verify_at(&token, NOW).is_ok());
+
+    // kid 与签名密钥不一致
+    let token = hs256_token(b"first", json!({ "alg": "HS256", "kid": "b" }), &claims());
+    assert!(verifier.verify_at(&token, NOW).is_err());
+
+    let token = hs256_token(b"first", json!({ "alg": "HS256", "kid": "c" }), &claims());
+    let err = verifier.verify_at(&token, NOW).unwrap_err();
+    assert!(err.to_string().contains("no HS256 key"));
+}
+
+#[test]
+fn tampered_or_malformed_tokens_are_rejected() {
+    let verifier = JwtVerifier::hs256(b"secret");
+    let token = hs256_token(b"secret", json!({ "alg": "HS256" }), &claims());
+
+    let mut parts: Vec<&str> = token.split('.').collect();
+    let forged = URL_SAFE_NO_PAD.encode(br#"{"sub":"admin","exp":1800000000,"permissions":["admin"]}"#);
+    parts[1] = &forged;
+    let err = verifier.verify_at(&parts.join("."), NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+
+    for malformed in ["", "abc", "a.b", "a.b.c.d", "!!.e30.sig"] {
+        let err = verifier.verify_at(malformed, NOW).unwrap_err();
+        assert_eq!(err.auth_code(), Some(AuthErrorCode::MalformedToken), "{:?}", malformed);
+    }
+}
+
+#[test]
+fn exp_is_required_unless_disabled() {
+    let claims = json!({ "sub": "user-1" });
+    let token = hs256_token(b"secret", json!({ "alg": "HS256" }), &claims);
+
+    let err = JwtVerifier::hs256(b"secret").verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::MalformedToken));
+
+    let verifier = JwtVerifier::hs256(b"secret").with_require_exp(false);
+    assert_eq!(verifier.verify_at(&token, NOW).unwrap().expires_at, u64::MAX);
+}
+
+#[test]
+fn nbf_and_scope_claims_are_honoured() {
+    let claims = json!({ "sub": "user-1", "exp": NOW + 600, "nbf": NOW + 120, "scope": "read stream:pumpfun" });
+    let token = hs256_token(b"secret", json!({ "alg": "HS256" }), &claims);
+    let verifier = JwtVerifier::hs256(b"secret").with_leeway(0);
+
+    let err = verifier.verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::TokenNotYetValid));
+
+    let claims = verifier.verify_at(&token, NOW + 120).unwrap();
+    assert_eq!(claims.permissions, ["read", "stream:pumpfun"]);
+}
+
+#[test]
+fn token_without_subject_is_rejected() {
+    let token = hs256_token(b"secret", json!({ "alg": "HS256" }), &json!({ "sub": "", "exp": NOW + 60 }));
+    let err = JwtVerifier::hs256(b"secret").verify_at(&token, NOW).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::MalformedToken));
+}
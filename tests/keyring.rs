@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use fzstream_common::{
+    AuthErrorCode, AuthTokenValidator, JwtHeader, Keyring, ServerConfig, SigningKey, SigningKeyConfig, TokenIssuer,
+};
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn stream() -> Vec<String> {
+    vec!["stream".to_string()]
+}
+
+#[test]
+fn rotated_keys_verify_until_retired() {
+    let keyring = Keyring::new();
+    keyring.add(SigningKey::hs256("k1", b"first"));
+    let old_token = keyring.issue("user-1", stream(), Duration::from_secs(3600), None).unwrap();
+
+    keyring.add(SigningKey::hs256("k2", b"second"));
+    keyring.set_primary("k2").unwrap();
+    let new_token = keyring.issue("user-1", stream(), Duration::from_secs(3600), None).unwrap();
+    assert_eq!(JwtHeader::decode(&new_token).unwrap().kid.as_deref(), Some("k2"));
+
+    let now = now_secs();
+    assert!(keyring.retire("k2", now).is_err());
+    keyring.retire("k1", now + 600).unwrap();
+    assert_eq!(keyring.verify_at(&old_token, now + 599).unwrap().key_id.as_deref(), Some("k1"));
+    assert!(keyring.verify_at(&new_token, now + 599).is_ok());
+
+    let err = keyring.verify_at(&old_token, now + 600).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+    assert!(keyring.verify_at(&new_token, now + 600).is_ok());
+    assert_eq!(keyring.purge_retired(now + 600), 1);
+    assert_eq!(keyring.kids(), vec!["k2".to_string()]);
+}
+
+#[test]
+fn server_config_keys_accept_old_and_legacy_tokens() {
+    let config = ServerConfig {
+        auth_secret_key: Some("legacy".to_string()),
+        signing_keys: vec![
+            SigningKeyConfig { kid: "k-old".to_string(), secret: "old".to_string(), primary: false, retire_at: None },
+            SigningKeyConfig { kid: "k-new".to_string(), secret: "new".to_string(), primary: true, retire_at: None },
+        ],
+        ..ServerConfig::default()
+    };
+    let validator = AuthTokenValidator::from_server_config(&config);
+
+    let issuer = TokenIssuer::from_server_config(&config).unwrap();
+    assert_eq!(issuer.kid(), Some("k-new"));
+    let lifetime = Duration::from_secs(60);
+    for token in [
+        issuer.issue("user-1", stream(), lifetime, None).unwrap(),
+        TokenIssuer::hs256(b"old").with_kid("k-old").issue("user-1", stream(), lifetime, None).unwrap(),
+        TokenIssuer::hs256(b"legacy").issue("user-1", stream(), lifetime, None).unwrap(),
+    ] {
+        assert_eq!(validator.validate_token(&token).unwrap(), stream());
+    }
+
+    let unknown = TokenIssuer::hs256(b"new").with_kid("k-missing").issue("user-1", stream(), lifetime, None).unwrap();
+    assert!(validator.validate_token(&unknown).is_err());
+}
+
+#[test]
+fn asymmetric_keys_derive_verification_key() {
+    use ring::signature::Ed25519KeyPair;
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+    let keyring = Keyring::new();
+    keyring.add(SigningKey::new("ed", TokenIssuer::ed25519_from_pkcs8(pkcs8.as_ref()).unwrap()));
+
+    let token = keyring.issue("user-1", stream(), Duration::from_secs(60), None).unwrap();
+    assert_eq!(keyring.verify(&token).unwrap().user_id, "user-1");
+}
+
+#[test]
+fn cached_verifier_follows_key_changes() {
+    let keyring = Keyring::new();
+    keyring.add(SigningKey::hs256("k1", b"first"));
+    let issuer = keyring.primary_issuer().unwrap();
+    assert_eq!(issuer.kid(), Some("k1"));
+
+    let token = issuer.issue("user-1", stream(), Duration::from_secs(3600), None).unwrap();
+    let unlabeled = TokenIssuer::hs256(b"first").issue("user-1", stream(), Duration::from_secs(3600), None).unwrap();
+    assert!(keyring.verify(&token).is_ok());
+
+    // 不带 kid 的令牌由已停用的密钥验证通过时同样拒绝
+    keyring.add(SigningKey::hs256("k2", b"second"));
+    keyring.set_primary("k2").unwrap();
+    let now = now_secs();
+    keyring.retire("k1", now).unwrap();
+    let err = keyring.verify_at(&unlabeled, now).unwrap_err();
+    assert!(err.to_string().contains("retired"));
+    assert!(keyring.verify_at(&unlabeled, now - 1).is_ok());
+
+    assert!(keyring.remove("k1"));
+    assert!(keyring.verify_at(&token, now - 1).is_err());
+
+    keyring.add(SigningKey::hs256("k1", b"first"));
+    assert!(keyring.verify_at(&token, now).is_ok());
+}
@@ -0,0 +1,62 @@
+use fzstream_common::{AuthErrorCode, EventType, EventTypeFilter, Permission, PermissionSet, ALL_EVENT_TYPES};
+
+fn strings(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn permission_strings_round_trip() {
+    for value in ["read", "write", "admin", "stream", "stream:PumpFun*", "stream:BlockMeta", "billing"] {
+        assert_eq!(Permission::parse(value).to_string(), value);
+    }
+    assert_eq!(Permission::parse("stream:RaydiumClmm*"), Permission::Stream("RaydiumClmm*".to_string()));
+    assert_eq!(Permission::parse("stream:"), Permission::Custom("stream:".to_string()));
+}
+
+#[test]
+fn scoped_stream_permission_limits_requested_filter() {
+    let permissions = PermissionSet::from_strings(&strings(&["read", "stream:PumpFun*"]));
+    assert!(permissions.allows_event(&EventType::PumpFunBuy));
+    assert!(!permissions.allows_event(&EventType::PumpSwapBuy));
+
+    let requested = EventTypeFilter::allow_only(vec![EventType::PumpFunBuy, EventType::RaydiumClmmSwap]);
+    let filter = permissions.authorize_filter(Some(&requested)).unwrap();
+    assert!(filter.is_allowed(&EventType::PumpFunBuy));
+    assert!(!filter.is_allowed(&EventType::RaydiumClmmSwap));
+
+    let everything = permissions.authorize_filter(None).unwrap();
+    assert!(everything.is_allowed(&EventType::PumpFunMigrate));
+    assert!(!everything.is_allowed(&EventType::AccountPumpFunGlobal));
+}
+
+#[test]
+fn unscoped_stream_and_admin_keep_requested_filter() {
+    let requested = EventTypeFilter::block_types(vec![EventType::BlockMeta]);
+    for list in [&["stream"][..], &["admin"][..]] {
+        let filter = PermissionSet::from_strings(&strings(list))
+            .authorize_filter(Some(&requested))
+            .unwrap();
+        assert!(filter.is_allowed(&EventType::PumpSwapSell));
+        assert!(!filter.is_allowed(&EventType::BlockMeta));
+    }
+}
+
+#[test]
+fn missing_or_disjoint_permissions_are_denied() {
+    let read_only = PermissionSet::from_strings(&strings(&["read"]));
+    let err = read_only.authorize_filter(None).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::PermissionDenied));
+
+    let bonk_only = PermissionSet::from_strings(&strings(&["stream:Bonk*"]));
+    let requested = EventTypeFilter::allow_only(vec![EventType::PumpFunBuy]);
+    let err = bonk_only.authorize_filter(Some(&requested)).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::PermissionDenied));
+}
+
+#[test]
+fn all_event_types_are_unique_and_exclude_unknown() {
+    assert!(!ALL_EVENT_TYPES.contains(&EventType::Unknown));
+    for (i, event_type) in ALL_EVENT_TYPES.iter().enumerate() {
+        assert!(!ALL_EVENT_TYPES[i + 1..].contains(event_type), "{:?} listed twice", event_type);
+    }
+}
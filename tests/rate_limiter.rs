@@ -0,0 +1,139 @@
+use std::time::{Duration, Instant};
+
+use fzstream_common::{AuthErrorCode, ClientQuota, JwtVerifier, RateLimiter, TokenBucket, TokenClaims, TokenIssuer};
+
+#[test]
+fn token_bucket_refills_over_time() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(10.0, 5.0);
+
+    for _ in 0..5 {
+        assert!(bucket.try_take_at(1.0, start));
+    }
+    assert!(!bucket.try_take_at(1.0, start));
+    assert!(bucket.try_take_at(1.0, start + Duration::from_millis(100)));
+    assert!(!bucket.try_take_at(1.0, start + Duration::from_millis(100)));
+}
+
+#[test]
+fn limiter_enforces_event_and_byte_rates() {
+    let limiter = RateLimiter::new();
+    let start = Instant::now();
+    limiter.set_quota(
+        "client-1",
+        ClientQuota { max_events_per_sec: Some(3), max_bytes_per_sec: Some(1_000), ..ClientQuota::default() },
+    );
+
+    assert!(limiter.try_send_at("client-1", 600, start));
+    // 字节额度不足时不应扣减事件额度
+    assert!(!limiter.try_send_at("client-1", 600, start));
+    assert!(limiter.try_send_at("client-1", 100, start));
+    assert!(limiter.try_send_at("client-1", 100, start));
+    assert!(!limiter.try_send_at("client-1", 1, start));
+    assert!(limiter.try_send_at("client-1", 1, start + Duration::from_secs(1)));
+
+    // 未设置配额的客户端不受限制
+    assert!(limiter.try_send_at("other", 1_000_000, start));
+}
+
+#[test]
+fn rate_limit_error_names_the_exceeded_quota() {
+    let limiter = RateLimiter::new();
+    let start = Instant::now();
+    limiter.set_quota(
+        "client-1",
+        ClientQuota { max_events_per_sec: Some(2), max_bytes_per_sec: Some(1_000), ..ClientQuota::default() },
+    );
+
+    limiter.check_send_at("client-1", 600, start).unwrap();
+    let err = limiter.check_send_at("client-1", 600, start).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::RateLimited));
+    assert!(err.to_string().contains("1000 bytes/s"));
+    assert!(!err.to_string().contains("events/s"));
+
+    limiter.check_send_at("client-1", 100, start).unwrap();
+    let err = limiter.check_send_at("client-1", 100, start).unwrap_err();
+    assert!(err.to_string().contains("2 events/s"));
+    assert!(!err.to_string().contains("bytes/s"));
+
+    let err = limiter.check_send_at("client-1", 900, start).unwrap_err();
+    assert!(err.to_string().contains("2 events/s and 1000 bytes/s"));
+}
+
+#[test]
+fn connection_quota_is_enforced() {
+    let limiter = RateLimiter::new();
+    limiter.set_quota("client-1", ClientQuota { max_connections: Some(2), ..ClientQuota::default() });
+
+    limiter.acquire_connection("client-1").unwrap();
+    limiter.acquire_connection("client-1").unwrap();
+    let err = limiter.acquire_connection("client-1").unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::ConnectionLimitExceeded));
+
+    limiter.release_connection("client-1");
+    assert_eq!(limiter.connections("client-1"), 1);
+    limiter.acquire_connection("client-1").unwrap();
+}
+
+#[test]
+fn quota_travels_in_jwt_claims() {
+    let quota = ClientQuota { max_connections: Some(4), max_events_per_sec: Some(500), max_bytes_per_sec: None };
+    let token = TokenIssuer::hs256(b"secret")
+        .sign_claims(&TokenClaims {
+            user_id: "user-1".to_string(),
+            permissions: vec!["stream".to_string()],
+            issued_at: 1_000,
+            expires_at: 2_000,
+            client_info: None,
+            quota: Some(quota),
+            token_id: None,
+            key_id: None,
+        })
+        .unwrap();
+
+    let claims = JwtVerifier::hs256(b"secret").verify_at(&token, 1_000).unwrap();
+    assert_eq!(claims.quota, Some(quota));
+}
+
+#[test]
+fn reapplying_the_same_quota_keeps_spent_buckets() {
+    let limiter = RateLimiter::new();
+    let start = Instant::now();
+    let quota = ClientQuota { max_events_per_sec: Some(1), max_connections: Some(1), ..ClientQuota::default() };
+    limiter.set_quota("user-1", quota);
+    limiter.acquire_connection("user-1").unwrap();
+    assert!(limiter.try_send_at("user-1", 1, start));
+
+    // 重新认证不会补满额度
+    limiter.set_quota("user-1", quota);
+    assert!(!limiter.try_send_at("user-1", 1, start));
+
+    // 配额变化时速率桶重新开始，连接数保留
+    limiter.set_quota("user-1", ClientQuota { max_events_per_sec: Some(2), ..quota });
+    assert!(limiter.try_send_at("user-1", 1, start));
+    assert_eq!(limiter.connections("user-1"), 1);
+}
+
+#[test]
+fn quota_from_claims_is_keyed_by_user_id() {
+    let limiter = RateLimiter::new();
+    let claims = TokenClaims {
+        user_id: "user-1".to_string(),
+        permissions: vec!["stream".to_string()],
+        issued_at: 1_000,
+        expires_at: 2_000,
+        client_info: Some("client-1".to_string()),
+        quota: Some(ClientQuota { max_connections: Some(1), ..ClientQuota::default() }),
+        token_id: None,
+        key_id: None,
+    };
+
+    let identity = limiter.set_quota_from_claims(&claims);
+    assert_eq!(identity, "user-1");
+    assert_eq!(limiter.quota("user-1"), claims.quota);
+    assert_eq!(limiter.quota("client-1"), None);
+
+    limiter.acquire_connection(identity).unwrap();
+    let err = limiter.acquire_connection(identity).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::ConnectionLimitExceeded));
+}
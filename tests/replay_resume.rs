@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use fzstream_common::{
+    CompressionLevel, EventMessage, EventType, ReplayBuffer, ResumeRequest, ResumeResponse, SequenceGenerator,
+    SequenceTracker, SerializationProtocol,
+};
+
+fn message(sequence: u64) -> EventMessage {
+    let mut message = EventMessage::new(
+        EventType::PumpFunBuy,
+        sequence.to_le_bytes().to_vec(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::None,
+        false,
+    );
+    message.set_sequence(sequence);
+    message
+}
+
+/// 序列号 1..=count，每两个事件一个 slot（slot 100、100、101、101……）
+fn filled_buffer(count: u64, max_events: usize) -> ReplayBuffer {
+    let mut buffer = ReplayBuffer::new(max_events, Duration::from_secs(60));
+    for sequence in 1..=count {
+        assert!(buffer.push(100 + (sequence - 1) / 2, message(sequence)));
+    }
+    buffer
+}
+
+fn sequences(messages: &[EventMessage]) -> Vec<u64> {
+    messages.iter().map(|message| message.sequence).collect()
+}
+
+#[test]
+fn resume_from_sequence_replays_missed_events() {
+    let buffer = filled_buffer(10, 100);
+
+    let (response, replay) = buffer.resume(&ResumeRequest { last_sequence: 7, last_slot: 0 });
+    assert_eq!(response, ResumeResponse::Resumed { from_sequence: 8, replay_count: 3 });
+    assert_eq!(sequences(&replay), [8, 9, 10]);
+}
+
+#[test]
+fn resume_at_latest_sequence_replays_nothing() {
+    let buffer = filled_buffer(10, 100);
+
+    let (response, replay) = buffer.resume(&ResumeRequest { last_sequence: 10, last_slot: 0 });
+    assert_eq!(response, ResumeResponse::Resumed { from_sequence: 11, replay_count: 0 });
+    assert!(replay.is_empty());
+}
+
+#[test]
+fn resume_requires_resync_when_events_were_evicted() {
+    let buffer = filled_buffer(10, 5);
+    assert_eq!(buffer.oldest_sequence(), Some(6));
+
+    // 6 仍在缓冲区中，可以续传
+    let (response, replay) = buffer.resume(&ResumeRequest { last_sequence: 5, last_slot: 0 });
+    assert_eq!(response, ResumeResponse::Resumed { from_sequence: 6, replay_count: 5 });
+    assert_eq!(sequences(&replay), [6, 7, 8, 9, 10]);
+
+    let (response, replay) = buffer.resume(&ResumeRequest { last_sequence: 4, last_slot: 0 });
+    assert!(replay.is_empty());
+    match response {
+        ResumeResponse::ResyncRequired { oldest_available, latest_sequence, .. } => {
+            assert_eq!(oldest_available, Some(6));
+            assert_eq!(latest_sequence, 10);
+        }
+        other => panic!("expected resync, got {:?}", other),
+    }
+}
+
+#[test]
+fn resume_ahead_of_server_requires_resync() {
+    let buffer = filled_buffer(10, 100);
+    let (response, _) = buffer.resume(&ResumeRequest { last_sequence: 11, last_slot: 0 });
+    assert!(matches!(response, ResumeResponse::ResyncRequired { .. }));
+
+    let (response, _) = buffer.resume(&ResumeRequest { last_sequence: 0, last_slot: 0 });
+    assert!(matches!(response, ResumeResponse::ResyncRequired { .. }));
+}
+
+#[test]
+fn resume_from_slot_replays_later_slots() {
+    let buffer = filled_buffer(10, 100);
+
+    let (response, replay) = buffer.resume(&ResumeRequest { last_sequence: 0, last_slot: 102 });
+    assert_eq!(response, ResumeResponse::Resumed { from_sequence: 7, replay_count: 4 });
+    assert_eq!(sequences(&replay), [7, 8, 9, 10]);
+
+    // slot 100 已被淘汰时无法保证完整
+    let buffer = filled_buffer(10, 7);
+    let (response, _) = buffer.resume(&ResumeRequest { last_sequence: 0, last_slot: 100 });
+    assert!(matches!(response, ResumeResponse::ResyncRequired { .. }));
+}
+
+#[test]
+fn buffer_ignores_unsequenced_and_stale_events() {
+    let mut buffer = filled_buffer(3, 100);
+    assert!(!buffer.push(200, message(0)));
+    assert!(!buffer.push(200, message(3)));
+    assert!(!buffer.push(200, message(2)));
+    assert!(buffer.push(200, message(5)));
+    assert_eq!(buffer.len(), 4);
+    assert_eq!(buffer.latest_sequence(), Some(5));
+}
+
+#[test]
+fn expired_events_are_not_replayed() {
+    let mut buffer = ReplayBuffer::new(100, Duration::from_millis(1));
+    buffer.push(1, message(1));
+    buffer.push(1, message(2));
+    std::thread::sleep(Duration::from_millis(5));
+
+    let (response, replay) = buffer.resume(&ResumeRequest { last_sequence: 1, last_slot: 0 });
+    assert!(matches!(response, ResumeResponse::ResyncRequired { .. }));
+    assert!(replay.is_empty());
+
+    buffer.evict_expired();
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn client_resumes_from_last_contiguous_sequence_after_gap() {
+    let generator = SequenceGenerator::new();
+    let mut buffer = ReplayBuffer::new(100, Duration::from_secs(60));
+    let sent: Vec<EventMessage> = (0..10).map(|_| message(generator.next_sequence())).collect();
+    for event in &sent {
+        buffer.push(1, event.clone());
+    }
+
+    // 客户端在 4 之后断线，只收到了 1..=4 和 6
+    let mut tracker = SequenceTracker::new();
+    for sequence in [1, 2, 3, 4, 6] {
+        tracker.observe(sequence);
+    }
+    let request = ResumeRequest {
+        last_sequence: tracker.last_contiguous().unwrap(),
+        last_slot: 0,
+    };
+    assert_eq!(request.last_sequence, 4);
+
+    let (response, replay) = buffer.resume(&request);
+    assert_eq!(response, ResumeResponse::Resumed { from_sequence: 5, replay_count: 6 });
+    for event in &replay {
+        tracker.observe(event.sequence);
+    }
+    assert!(tracker.missing().is_empty());
+    assert_eq!(tracker.last_contiguous(), Some(10));
+    assert_eq!(tracker.stats().duplicates, 1);
+}
+
+#[test]
+fn last_contiguous_stays_at_untracked_gap_until_reset() {
+    let mut tracker = SequenceTracker::with_limits(4, 256);
+    for sequence in [1, 2, 3] {
+        tracker.observe(sequence);
+    }
+    // 缺口 4..100 超出跟踪上限，缺失序列号不会逐一记录
+    tracker.observe(100);
+    assert!(tracker.missing().is_empty());
+    assert!(tracker.needs_resync());
+    assert_eq!(tracker.last_contiguous(), Some(3));
+
+    tracker.observe(101);
+    tracker.observe(103);
+    assert_eq!(tracker.missing(), [102]);
+    assert_eq!(tracker.last_contiguous(), Some(3));
+
+    tracker.reset(103);
+    assert!(!tracker.needs_resync());
+    assert_eq!(tracker.last_contiguous(), Some(103));
+}
+
+#[test]
+fn generator_reports_nothing_assigned_before_first_sequence() {
+    let generator = SequenceGenerator::starting_at(100);
+    assert_eq!(generator.last_assigned(), 0);
+    assert_eq!(generator.next_sequence(), 100);
+    assert_eq!(generator.last_assigned(), 100);
+
+    let generator = SequenceGenerator::new();
+    assert_eq!(generator.last_assigned(), 0);
+    assert_eq!(generator.next_range(5), 1);
+    assert_eq!(generator.last_assigned(), 5);
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use fzstream_common::{
+    ApiKeyManager, AuthErrorCode, AuthTokenValidator, JwtKey, JwtVerifier, Keyring, MemoryApiKeyStore,
+    RevocationList, RevokedIds, SigningKey, TokenIssuer,
+};
+
+fn stream() -> Vec<String> {
+    vec!["stream".to_string()]
+}
+
+#[test]
+fn revoked_jwt_ids_users_and_keys_are_rejected() {
+    let revocations = Arc::new(RevocationList::new());
+    let validator = AuthTokenValidator::new()
+        .with_jwt_verifier(JwtVerifier::new().with_key(JwtKey::hs256(b"secret").with_kid("k1")))
+        .with_revocation_list(revocations.clone());
+
+    let issuer = TokenIssuer::hs256(b"secret").with_kid("k1");
+    let first = issuer.issue("user-1", stream(), Duration::from_secs(60), None).unwrap();
+    let second = issuer.issue("user-1", stream(), Duration::from_secs(60), None).unwrap();
+    let claims = validator.validate_jwt(&first).unwrap();
+    assert_eq!(claims.key_id.as_deref(), Some("k1"));
+
+    revocations.revoke_token(claims.token_id.unwrap());
+    let err = validator.validate_token(&first).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::TokenRevoked));
+    assert!(validator.validate_token(&second).is_ok());
+
+    revocations.revoke_key("k1");
+    assert!(validator.validate_token(&second).is_err());
+
+    revocations.replace(RevokedIds::default());
+    revocations.revoke_user("user-1");
+    assert!(validator.validate_token(&second).is_err());
+}
+
+#[test]
+fn revoked_api_key_ids_are_rejected() {
+    let api_keys = ApiKeyManager::new(Arc::new(MemoryApiKeyStore::new()));
+    let issued = api_keys.create("bot", stream(), None).unwrap();
+    let revocations = Arc::new(RevocationList::new());
+    let validator = AuthTokenValidator::new()
+        .with_api_keys(api_keys)
+        .with_revocation_list(revocations.clone());
+
+    assert!(validator.validate_token(&issued.key).is_ok());
+    // JWT 签名密钥编号与 API 密钥编号互不影响
+    revocations.revoke_key(issued.record.key_id.clone());
+    assert!(validator.validate_token(&issued.key).is_ok());
+
+    revocations.revoke_api_key(issued.record.key_id.clone());
+    let err = validator.validate_token(&issued.key).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::TokenRevoked));
+}
+
+#[test]
+fn revoked_users_api_keys_are_rejected() {
+    let api_keys = ApiKeyManager::new(Arc::new(MemoryApiKeyStore::new()));
+    let issued = api_keys.create("bot", stream(), None).unwrap();
+    let other = api_keys.create("other-bot", stream(), None).unwrap();
+    let revocations = Arc::new(RevocationList::new());
+    let validator = AuthTokenValidator::new()
+        .with_api_keys(api_keys)
+        .with_revocation_list(revocations.clone());
+
+    revocations.revoke_user("bot");
+    let err = validator.validate_token(&issued.key).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::TokenRevoked));
+    assert!(validator.validate_token(&other.key).is_ok());
+}
+
+#[test]
+fn key_revocation_uses_the_verifying_key_not_the_header() {
+    let revocations = Arc::new(RevocationList::new());
+    revocations.revoke_key("k-revoked");
+    let keyring = Arc::new(Keyring::new());
+    keyring.add(SigningKey::hs256("k1", b"one"));
+    keyring.add(SigningKey::hs256("k-revoked", b"revoked"));
+    let validator = AuthTokenValidator::new()
+        .with_keyring(keyring)
+        .with_revocation_list(revocations.clone());
+
+    // 不带 kid 的令牌仍按实际验证通过的密钥检查吊销
+    let token = TokenIssuer::hs256(b"revoked").issue("user-1", stream(), Duration::from_secs(60), None).unwrap();
+    let err = validator.validate_token(&token).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::TokenRevoked));
+
+    let token = TokenIssuer::hs256(b"one").issue("user-1", stream(), Duration::from_secs(60), None).unwrap();
+    assert_eq!(validator.validate_jwt(&token).unwrap().key_id.as_deref(), Some("k1"));
+
+    // 未配置编号的密钥接受任意头部 kid，但不会把它当作密钥编号
+    let token = TokenIssuer::hs256(b"secret")
+        .with_kid("forged")
+        .issue("user-1", stream(), Duration::from_secs(60), None)
+        .unwrap();
+    let claims = JwtVerifier::hs256(b"secret").verify(&token).unwrap();
+    assert_eq!(claims.key_id, None);
+}
+
+#[test]
+fn reload_picks_up_file_changes_in_place() {
+    let path = std::env::temp_dir().join(format!("fzstream-revocations-{}.json", std::process::id()));
+    std::fs::write(&path, "{}").unwrap();
+
+    let revocations = Arc::new(RevocationList::open(&path).unwrap());
+    assert!(revocations.check(Some("t1"), None, None).is_ok());
+
+    std::fs::write(&path, r#"{"token_ids": ["t1"]}"#).unwrap();
+    revocations.reload().unwrap();
+    assert!(revocations.check(Some("t1"), None, None).is_err());
+
+    // 解析失败时保留原有内容
+    std::fs::write(&path, "not json").unwrap();
+    assert!(revocations.reload().is_err());
+    assert!(revocations.check(Some("t1"), None, None).is_err());
+
+    revocations.replace(RevokedIds::default());
+    revocations.revoke_user("user-2");
+    revocations.save().unwrap();
+    let reopened = RevocationList::open(&path).unwrap();
+    assert!(reopened.snapshot().user_ids.contains("user-2"));
+
+    std::fs::remove_file(&path).unwrap();
+}
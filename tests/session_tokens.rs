@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use fzstream_common::{AuthErrorCode, AuthTokenValidator, ServerConfig, SessionManager};
+
+#[test]
+fn issued_session_is_accepted_until_ttl() {
+    let sessions = SessionManager::new(b"session-secret", Duration::from_secs(600));
+    let token = sessions.issue("user-1", vec!["stream".to_string()]).unwrap();
+    let claims = sessions.verify(&token).unwrap();
+
+    let validator = AuthTokenValidator::new().with_sessions(Arc::new(sessions));
+    assert_eq!(validator.validate_token(&token).unwrap(), vec!["stream".to_string()]);
+
+    let sessions = validator.sessions().unwrap();
+    let expired = sessions.verify_at(&token, claims.expires_at).unwrap_err();
+    assert_eq!(expired.auth_code(), Some(AuthErrorCode::ExpiredToken));
+
+    // 时间戳在未来的令牌不能导致溢出
+    let early = sessions.verify_at(&token, 0).unwrap_err();
+    assert_eq!(early.auth_code(), Some(AuthErrorCode::TokenNotYetValid));
+}
+
+#[test]
+fn forged_sessions_are_rejected() {
+    let sessions = SessionManager::new(b"session-secret", Duration::from_secs(600));
+    let token = sessions.issue("user-1", vec!["stream".to_string()]).unwrap();
+
+    let (payload, signature) = token.trim_start_matches("sess_").split_once('.').unwrap();
+    let forged_claims = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).unwrap())
+        .unwrap()
+        .replace("user-1", "admin");
+    let forged = format!("sess_{}.{}", URL_SAFE_NO_PAD.encode(forged_claims), signature);
+    let err = sessions.verify(&forged).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+
+    let other = SessionManager::new(b"other-secret", Duration::from_secs(600));
+    assert!(other.verify(&token).is_err());
+    assert!(sessions.verify("sess_1700000000_user_random").is_err());
+}
+
+#[test]
+fn refresh_keeps_session_and_revocation_covers_refreshed_tokens() {
+    let sessions = SessionManager::new(b"session-secret", Duration::from_secs(600));
+    let token = sessions.issue("user-1", vec!["stream".to_string()]).unwrap();
+    let refreshed = sessions.refresh(&token).unwrap();
+
+    let original = sessions.verify(&token).unwrap();
+    let renewed = sessions.verify(&refreshed).unwrap();
+    assert_eq!(original.session_id, renewed.session_id);
+    assert!(renewed.expires_at >= original.expires_at);
+
+    sessions.revoke(&token).unwrap();
+    let err = sessions.verify(&token).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::TokenRevoked));
+    assert!(sessions.verify(&refreshed).is_err());
+    assert!(sessions.refresh(&refreshed).is_err());
+}
+
+#[test]
+fn server_config_derives_session_key_from_auth_secret() {
+    let config = ServerConfig {
+        auth_secret_key: Some("shared-secret".to_string()),
+        ..ServerConfig::default()
+    };
+    let validator = AuthTokenValidator::from_server_config(&config);
+    let token = validator.sessions().unwrap().issue("user-1", vec!["stream".to_string()]).unwrap();
+    assert_eq!(validator.validate_token(&token).unwrap(), vec!["stream".to_string()]);
+
+    // 会话令牌不使用原始密钥签名
+    let raw = SessionManager::new(b"shared-secret", Duration::from_secs(600));
+    let err = raw.verify(&token).unwrap_err();
+    assert_eq!(err.auth_code(), Some(AuthErrorCode::InvalidToken));
+}
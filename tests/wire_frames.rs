@@ -0,0 +1,332 @@
+use serde::{Deserialize, Serialize};
+
+use fzstream_common::{
+    decode_frame, encode_frame, encode_message, CompressionLevel, EventBatch, EventBatchBuilder, EventMessage, EventType,
+    FrameDecoder, FrameError, FrameHeader, FrameKind, SerializationProtocol, FRAME_HEADER_LEN, WIRE_VERSION_MAJOR, WIRE_VERSION_MINOR,
+};
+
+fn sample_message() -> EventMessage {
+    EventMessage::new(
+        EventType::PumpFunBuy,
+        b"event payload".to_vec(),
+        SerializationProtocol::Bincode,
+        CompressionLevel::None,
+        false,
+    )
+}
+
+fn message_with_data(data: &[u8]) -> EventMessage {
+    let mut message = sample_message();
+    message.data = data.to_vec();
+    message
+}
+
+/// 改写帧头中的版本号
+fn with_version(frame: &[u8], major: u8, minor: u8) -> Vec<u8> {
+    let mut header = FrameHeader::parse(frame).unwrap();
+    header.version_major = major;
+    header.version_minor = minor;
+    let mut rewritten = Vec::with_capacity(frame.len());
+    header.write_to(&mut rewritten);
+    rewritten.extend_from_slice(&frame[FRAME_HEADER_LEN..]);
+    rewritten
+}
+
+#[test]
+fn frame_roundtrips_with_current_version() {
+    let message = sample_message();
+    let frame = encode_frame(&message).unwrap();
+
+    let header = FrameHeader::parse(&frame).unwrap();
+    assert_eq!(header.version_major, WIRE_VERSION_MAJOR);
+    assert_eq!(header.version_minor, WIRE_VERSION_MINOR);
+
+    let (decoded, consumed) = decode_frame(&frame).unwrap();
+    assert_eq!(consumed, frame.len());
+    assert_eq!(decoded.event_id, message.event_id);
+    assert_eq!(decoded.data, message.data);
+}
+
+#[test]
+fn newer_minor_version_is_accepted() {
+    let frame = encode_frame(&sample_message()).unwrap();
+
+    for minor in [WIRE_VERSION_MINOR + 1, u8::MAX] {
+        let (decoded, _) = decode_frame(&with_version(&frame, WIRE_VERSION_MAJOR, minor)).unwrap();
+        assert_eq!(decoded.data, b"event payload");
+    }
+}
+
+/// 为负载构造次版本 0 的帧
+fn v0_frame(kind: FrameKind, payload: &[u8]) -> Vec<u8> {
+    let mut header = FrameHeader::new(payload, kind.id() << 4, 0, SerializationProtocol::Bincode.wire_id()).unwrap();
+    header.version_minor = 0;
+    let mut frame = Vec::new();
+    header.write_to(&mut frame);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// 次版本 0 的 `EventMessage` 布局
+#[derive(Serialize, Deserialize)]
+struct EventMessageV0 {
+    event_id: String,
+    event_type: EventType,
+    data: Vec<u8>,
+    timestamp: u64,
+    serialization_format: SerializationProtocol,
+    compression_format: CompressionLevel,
+    is_compressed: bool,
+    original_size: Option<usize>,
+    grpc_arrival_time: u64,
+    parsing_time: u64,
+    completion_time: u64,
+    client_processing_start: Option<u64>,
+    client_processing_end: Option<u64>,
+}
+
+#[test]
+fn old_minor_version_event_layout_is_decoded() {
+    let v0 = EventMessageV0 {
+        event_id: "evt-1".to_string(),
+        event_type: EventType::PumpFunSell,
+        data: b"old payload".to_vec(),
+        timestamp: 1_700_000_000_000,
+        serialization_format: SerializationProtocol::Bincode,
+        compression_format: CompressionLevel::None,
+        is_compressed: false,
+        original_size: None,
+        grpc_arrival_time: 10,
+        parsing_time: 20,
+        completion_time: 30,
+        client_processing_start: None,
+        client_processing_end: Some(40),
+    };
+    let frame = v0_frame(FrameKind::Event, &bincode::serialize(&v0).unwrap());
+
+    let (decoded, consumed) = decode_frame(&frame).unwrap();
+    assert_eq!(consumed, frame.len());
+    assert_eq!(decoded.event_id, "evt-1");
+    assert_eq!(decoded.event_type, EventType::PumpFunSell);
+    assert_eq!(decoded.data, b"old payload");
+    assert_eq!(decoded.completion_time, 30);
+    assert_eq!(decoded.client_processing_end, Some(40));
+    assert_eq!(decoded.dictionary_id, None);
+    assert_eq!(decoded.sequence, 0);
+
+    let mut decoder = FrameDecoder::new();
+    assert_eq!(decoder.decode(&frame).unwrap()[0].event_id, "evt-1");
+}
+
+#[test]
+fn current_event_layout_is_readable_by_old_decoder() {
+    // 新字段追加在末尾，旧版本按旧布局读取时忽略多出的字节
+    let mut message = sample_message();
+    message.set_sequence(42);
+    let frame = encode_frame(&message).unwrap();
+
+    let v0: EventMessageV0 = bincode::deserialize(&frame[FRAME_HEADER_LEN..]).unwrap();
+    assert_eq!(v0.event_id, message.event_id);
+    assert_eq!(v0.data, message.data);
+    assert_eq!(v0.grpc_arrival_time, message.grpc_arrival_time);
+}
+
+#[test]
+fn sequence_numbers_survive_frame_roundtrip() {
+    let mut message = sample_message();
+    message.set_sequence(42);
+    let (decoded, _) = decode_frame(&encode_frame(&message).unwrap()).unwrap();
+    assert_eq!(decoded.sequence, 42);
+
+    let mut builder = EventBatchBuilder::new(7, SerializationProtocol::Bincode, CompressionLevel::None);
+    builder.push(EventType::PumpFunBuy, b"a").unwrap();
+    builder.push(EventType::PumpFunSell, b"b").unwrap();
+    builder.set_first_sequence(100);
+    let frame = encode_message(&builder.build()).unwrap();
+
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&frame);
+    let batch: EventBatch = decoder.next_typed().unwrap().unwrap();
+    assert_eq!(batch.first_sequence, 100);
+    assert_eq!(batch.last_sequence(), Some(101));
+}
+
+/// 次版本 0 的 `EventBatch` 布局
+#[derive(Serialize)]
+struct EventBatchV0 {
+    slot: u64,
+    timestamp: u64,
+    serialization_format: SerializationProtocol,
+    compression_format: CompressionLevel,
+    is_compressed: bool,
+    original_size: Option<usize>,
+    dictionary_id: Option<u32>,
+    event_types: Vec<EventType>,
+    offsets: Vec<u32>,
+    data: Vec<u8>,
+}
+
+#[test]
+fn old_minor_version_batch_layout_is_decoded() {
+    let v0 = EventBatchV0 {
+        slot: 9,
+        timestamp: 1_700_000_000_000,
+        serialization_format: SerializationProtocol::Bincode,
+        compression_format: CompressionLevel::None,
+        is_compressed: false,
+        original_size: None,
+        dictionary_id: None,
+        event_types: vec![EventType::PumpFunBuy, EventType::PumpFunSell],
+        offsets: vec![0, 3],
+        data: b"onetwo".to_vec(),
+    };
+    let frame = v0_frame(FrameKind::Batch, &bincode::serialize(&v0).unwrap());
+
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&frame);
+    let batch: EventBatch = decoder.next_typed().unwrap().unwrap();
+    assert_eq!(batch.slot, 9);
+    assert_eq!(batch.first_sequence, 0);
+    assert_eq!(batch.last_sequence(), None);
+
+    let decoded = batch.decode().unwrap();
+    let data: Vec<&[u8]> = decoded.iter().map(|event| event.data).collect();
+    assert_eq!(data, [&b"one"[..], b"two"]);
+}
+
+#[test]
+fn different_major_version_is_rejected() {
+    let frame = encode_frame(&sample_message()).unwrap();
+    let major = WIRE_VERSION_MAJOR + 1;
+
+    let err = decode_frame(&with_version(&frame, major, 0)).unwrap_err();
+    assert_eq!(err, FrameError::UnsupportedVersion { major, minor: 0 });
+    assert_eq!(err.code(), 1102);
+}
+
+#[test]
+fn longer_header_from_newer_minor_version_is_skipped() {
+    let frame = encode_frame(&sample_message()).unwrap();
+    let mut header = FrameHeader::parse(&frame).unwrap();
+    header.version_minor = WIRE_VERSION_MINOR + 1;
+    header.header_len = (FRAME_HEADER_LEN + 2) as u8;
+
+    let mut extended = Vec::new();
+    header.write_to(&mut extended);
+    extended.extend_from_slice(&[0xAA, 0xBB]);
+    extended.extend_from_slice(&frame[FRAME_HEADER_LEN..]);
+
+    let (decoded, consumed) = decode_frame(&extended).unwrap();
+    assert_eq!(consumed, extended.len());
+    assert_eq!(decoded.data, b"event payload");
+}
+
+#[test]
+fn decoder_waits_for_partial_frame() {
+    let frame = encode_frame(&sample_message()).unwrap();
+    let mut decoder = FrameDecoder::new();
+
+    // 帧头不完整
+    assert!(decoder.decode(&frame[..FRAME_HEADER_LEN - 1]).unwrap().is_empty());
+    // 帧头完整但负载不完整
+    assert!(decoder.decode(&frame[FRAME_HEADER_LEN - 1..frame.len() - 1]).unwrap().is_empty());
+    assert_eq!(decoder.buffered_len(), frame.len() - 1);
+
+    let messages = decoder.decode(&frame[frame.len() - 1..]).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].data, b"event payload");
+    assert_eq!(decoder.buffered_len(), 0);
+    decoder.finish().unwrap();
+}
+
+#[test]
+fn decoder_returns_every_frame_in_one_chunk() {
+    let mut chunk = Vec::new();
+    for data in [&b"first"[..], b"second", b"third"] {
+        chunk.extend(encode_frame(&message_with_data(data)).unwrap());
+    }
+    let next = encode_frame(&message_with_data(b"fourth")).unwrap();
+    chunk.extend_from_slice(&next[..5]);
+
+    let mut decoder = FrameDecoder::new();
+    let messages = decoder.decode(&chunk).unwrap();
+    let data: Vec<&[u8]> = messages.iter().map(|message| message.data.as_slice()).collect();
+    assert_eq!(data, [&b"first"[..], b"second", b"third"]);
+    assert!(matches!(decoder.finish(), Err(FrameError::Truncated { .. })));
+}
+
+#[test]
+fn decoder_reports_checksum_mismatch_after_returning_earlier_frames() {
+    let good = encode_frame(&message_with_data(b"good")).unwrap();
+    let mut corrupt = encode_frame(&message_with_data(b"corrupt")).unwrap();
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xFF;
+    let after = encode_frame(&message_with_data(b"after")).unwrap();
+
+    let mut chunk = good.clone();
+    chunk.extend_from_slice(&corrupt);
+    chunk.extend_from_slice(&after);
+
+    let mut decoder = FrameDecoder::new();
+    let messages = decoder.decode(&chunk).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].data, b"good");
+
+    let err = decoder.decode(&[]).unwrap_err();
+    assert!(matches!(err, FrameError::ChecksumMismatch { .. }));
+    assert_eq!(err.code(), 1106);
+
+    // 错误帧已被丢弃，后续帧仍可解码
+    let messages = decoder.decode(&[]).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].data, b"after");
+    decoder.finish().unwrap();
+}
+
+#[test]
+fn decoder_reports_checksum_mismatch_immediately_when_nothing_decoded() {
+    let mut corrupt = encode_frame(&sample_message()).unwrap();
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xFF;
+
+    let mut decoder = FrameDecoder::new();
+    assert!(matches!(decoder.decode(&corrupt), Err(FrameError::ChecksumMismatch { .. })));
+    assert_eq!(decoder.buffered_len(), 0);
+}
+
+#[test]
+fn reading_frame_as_wrong_kind_keeps_it_in_buffer() {
+    let frame = encode_frame(&sample_message()).unwrap();
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&frame);
+
+    let err = decoder.next_typed::<EventBatch>().unwrap_err();
+    assert_eq!(
+        err,
+        FrameError::UnexpectedKind { expected: FrameKind::Batch.id(), actual: FrameKind::Event.id() }
+    );
+    assert_eq!(decoder.buffered_len(), frame.len());
+    assert_eq!(decoder.peek_kind(), Some(FrameKind::Event));
+
+    let message = decoder.next_message().unwrap().unwrap();
+    assert_eq!(message.data, b"event payload");
+    assert_eq!(decoder.buffered_len(), 0);
+}
+
+#[test]
+fn mixed_event_and_batch_frames_are_read_by_kind() {
+    let mut builder = EventBatchBuilder::new(1, SerializationProtocol::Bincode, CompressionLevel::None);
+    builder.push(EventType::PumpFunBuy, b"batched").unwrap();
+
+    let mut stream = encode_message(&builder.build()).unwrap();
+    stream.extend(encode_frame(&sample_message()).unwrap());
+
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&stream);
+    assert!(decoder.next_message().is_err());
+    let batch: EventBatch = decoder.next_typed().unwrap().unwrap();
+    assert_eq!(batch.len(), 1);
+    assert!(decoder.next_typed::<EventBatch>().is_err());
+    assert_eq!(decoder.next_message().unwrap().unwrap().data, b"event payload");
+    decoder.finish().unwrap();
+}